@@ -0,0 +1,64 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use anyhow::Context;
+use sui_rest_api::CheckpointData;
+use sui_types::{
+    committee::Committee,
+    digests::TransactionDigest,
+    effects::{TransactionEffects, TransactionEvents},
+    messages_checkpoint::CertifiedCheckpointSummary,
+};
+
+use crate::verifier::{
+    check_contiguous_epochs, extract_verified_effects_and_events, verify_end_of_epoch_summary,
+};
+
+/// A self-contained proof of a transaction: the end-of-epoch checkpoints chaining the
+/// committees from genesis to the epoch of the full checkpoint that includes it.
+///
+/// The genesis committee is not part of the bundle, the recipient checks the bundle
+/// against the one from their own genesis file.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProofBundle {
+    pub transaction: TransactionDigest,
+    /// The last checkpoint of every epoch before the one of `full_checkpoint`, in order
+    pub end_of_epoch_checkpoints: Vec<CertifiedCheckpointSummary>,
+    pub full_checkpoint: CheckpointData,
+}
+
+impl ProofBundle {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        bcs::to_bytes(self).context("Unable to serialize proof bundle")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        bcs::from_bytes(bytes).context("Unable to parse proof bundle")
+    }
+
+    /// Verifies the committee chain from `genesis` and the transaction in the full
+    /// checkpoint, returning its effects and events. Needs no network access.
+    pub fn verify(
+        &self,
+        genesis: &Committee,
+    ) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
+        check_contiguous_epochs(&self.end_of_epoch_checkpoints, genesis.epoch)?;
+        let mut committee = genesis.clone();
+        for summary in &self.end_of_epoch_checkpoints {
+            committee = verify_end_of_epoch_summary(summary, &committee)?;
+        }
+
+        let epoch = self.full_checkpoint.checkpoint_summary.epoch();
+        anyhow::ensure!(
+            committee.epoch == epoch,
+            "Bundle chains committees up to epoch {} but its checkpoint is in epoch {}",
+            committee.epoch,
+            epoch
+        );
+        Ok(extract_verified_effects_and_events(
+            &self.full_checkpoint,
+            &committee,
+            self.transaction,
+        )?)
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::anyhow;
+use sui_types::messages_checkpoint::CertifiedCheckpointSummary;
+
+const DATA_FILE: &str = "checkpoints.bcs";
+const INDEX_FILE: &str = "checkpoints.idx";
+
+/// Size of an index entry, the sequence number and the offset of its record in the data
+/// file as little endian u64s
+const INDEX_ENTRY_LEN: usize = 16;
+
+/// Bit of the indexed offset set for checkpoints that are not end-of-epoch. Stores written
+/// before ordinary checkpoints could be cached only hold end-of-epoch ones, without it.
+const NOT_END_OF_EPOCH_FLAG: u64 = 1 << 63;
+
+/// Where a stored checkpoint is, and whether it is an end-of-epoch checkpoint
+#[derive(Clone, Copy)]
+struct IndexEntry {
+    offset: u64,
+    end_of_epoch: bool,
+}
+
+/// Verified checkpoint summaries stored in a single append-only file of length prefixed
+/// BCS records, with an append-only index from sequence number to record offset that is
+/// kept in memory.
+///
+/// Both end-of-epoch checkpoints, the trust chain listed in `checkpoints.yaml`, and
+/// ordinary checkpoints cached by users are stored, told apart in the index.
+pub struct CheckpointStore {
+    dir: PathBuf,
+    index: Mutex<BTreeMap<u64, IndexEntry>>,
+}
+
+impl CheckpointStore {
+    /// Opens the store in `dir`, loading its index. A missing store is empty.
+    pub fn open(dir: &Path) -> anyhow::Result<Self> {
+        let mut index = BTreeMap::new();
+        let index_path = dir.join(INDEX_FILE);
+        if index_path.exists() {
+            let mut bytes = fs::read(&index_path)?;
+
+            // Drop an entry left partially written by an interrupted append
+            let complete_len = bytes.len() - bytes.len() % INDEX_ENTRY_LEN;
+            if complete_len != bytes.len() {
+                bytes.truncate(complete_len);
+                OpenOptions::new()
+                    .write(true)
+                    .open(&index_path)?
+                    .set_len(complete_len as u64)?;
+            }
+
+            for entry in bytes.chunks_exact(INDEX_ENTRY_LEN) {
+                let (seq, offset) = entry.split_at(8);
+                let offset = u64::from_le_bytes(offset.try_into()?);
+                index.insert(
+                    u64::from_le_bytes(seq.try_into()?),
+                    IndexEntry {
+                        offset: offset & !NOT_END_OF_EPOCH_FLAG,
+                        end_of_epoch: offset & NOT_END_OF_EPOCH_FLAG == 0,
+                    },
+                );
+            }
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            index: Mutex::new(index),
+        })
+    }
+
+    pub fn contains(&self, seq: u64) -> bool {
+        self.index.lock().unwrap().contains_key(&seq)
+    }
+
+    /// Sequence numbers of the stored checkpoints, in ascending order
+    pub fn sequence_numbers(&self) -> Vec<u64> {
+        self.index.lock().unwrap().keys().copied().collect()
+    }
+
+    /// Sequence numbers of the stored end-of-epoch checkpoints, in ascending order
+    pub fn end_of_epoch_sequence_numbers(&self) -> Vec<u64> {
+        self.index
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.end_of_epoch)
+            .map(|(seq, _)| *seq)
+            .collect()
+    }
+
+    pub fn get(&self, seq: u64) -> anyhow::Result<Option<CertifiedCheckpointSummary>> {
+        let Some(IndexEntry { offset, .. }) = self.index.lock().unwrap().get(&seq).copied() else {
+            return Ok(None);
+        };
+
+        let mut data = File::open(self.dir.join(DATA_FILE))?;
+        data.seek(SeekFrom::Start(offset))?;
+        let mut len = [0u8; 8];
+        data.read_exact(&mut len)?;
+        let mut bytes = vec![0; u64::from_le_bytes(len) as usize];
+        data.read_exact(&mut bytes)?;
+        let summary = bcs::from_bytes(&bytes)
+            .map_err(|_| anyhow!("Unable to parse stored checkpoint {}", seq))?;
+        Ok(Some(summary))
+    }
+
+    /// Appends a checkpoint summary to the store, unless it is already stored.
+    pub fn insert(&self, summary: &CertifiedCheckpointSummary) -> anyhow::Result<()> {
+        let mut index = self.index.lock().unwrap();
+        let seq = summary.sequence_number;
+        if index.contains_key(&seq) {
+            return Ok(());
+        }
+
+        let bytes = bcs::to_bytes(summary)
+            .map_err(|_| anyhow!("Unable to serialize checkpoint summary"))?;
+        let mut data = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(DATA_FILE))?;
+        let offset = data.metadata()?.len();
+        data.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        data.write_all(&bytes)?;
+        data.sync_data()?;
+
+        // Index the record only once it is fully written
+        let end_of_epoch = summary.end_of_epoch_data.is_some();
+        let flags = if end_of_epoch {
+            0
+        } else {
+            NOT_END_OF_EPOCH_FLAG
+        };
+        let mut entry = seq.to_le_bytes().to_vec();
+        entry.extend_from_slice(&(offset | flags).to_le_bytes());
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(INDEX_FILE))?
+            .write_all(&entry)?;
+        index.insert(
+            seq,
+            IndexEntry {
+                offset,
+                end_of_epoch,
+            },
+        );
+        Ok(())
+    }
+
+    /// Imports the checkpoints stored as one `<seq>.yaml` file of BCS bytes each by
+    /// earlier versions, returning how many were added. The old files are kept.
+    pub fn import_legacy_files(&self) -> anyhow::Result<usize> {
+        let mut legacy = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "yaml") {
+                if let Some(seq) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse::<u64>().ok())
+                {
+                    legacy.push((seq, path));
+                }
+            }
+        }
+        legacy.sort();
+
+        let mut imported = 0;
+        for (seq, path) in legacy {
+            if self.contains(seq) {
+                continue;
+            }
+            let summary = read_legacy_file(&path)?;
+            self.insert(&summary)?;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+}
+
+/// Reads a checkpoint summary stored as a `<seq>.yaml` file of BCS bytes.
+pub fn read_legacy_file(path: &Path) -> anyhow::Result<CertifiedCheckpointSummary> {
+    let bytes = fs::read(path)?;
+    bcs::from_bytes(&bytes)
+        .map_err(|_| anyhow!("Unable to parse checkpoint file {}", path.display()))
+}
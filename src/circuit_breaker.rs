@@ -0,0 +1,81 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// State of a [`CircuitBreaker`], as reported by
+/// [`LightClient::dwallet_circuit_state`](crate::LightClient::dwallet_circuit_state).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum CircuitState {
+    /// Requests go through, after `consecutive_failures` failed ones in a row
+    Closed { consecutive_failures: u32 },
+    /// Requests fail fast until the cooldown elapses, then one is let through
+    Open { consecutive_failures: u32 },
+}
+
+struct Inner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Counts the consecutive failed requests to an endpoint and opens once they reach
+/// `threshold`, failing requests fast for `cooldown` instead of waiting on each timeout.
+pub(crate) struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            inner: Mutex::new(Inner {
+                consecutive_failures: 0,
+                opened_at: None,
+            }),
+        }
+    }
+
+    /// Whether a request may be made. Once the cooldown of an open breaker elapsed, a
+    /// trial request is let through and a new failure opens it again.
+    pub fn allows_request(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        match inner.opened_at {
+            Some(opened_at) => opened_at.elapsed() >= self.cooldown,
+            None => true,
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+    }
+
+    /// Records a failed request, returning whether the breaker is open afterwards.
+    pub fn record_failure(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        if inner.consecutive_failures >= self.threshold {
+            inner.opened_at = Some(Instant::now());
+        }
+        inner.opened_at.is_some()
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap();
+        let consecutive_failures = inner.consecutive_failures;
+        match inner.opened_at {
+            Some(_) => CircuitState::Open {
+                consecutive_failures,
+            },
+            None => CircuitState::Closed {
+                consecutive_failures,
+            },
+        }
+    }
+}
@@ -0,0 +1,3340 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use anyhow::{anyhow, Context};
+use backoff::ExponentialBackoff;
+use fastcrypto::encoding::{Base64, Encoding};
+use futures::stream::{self, StreamExt};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use object_store::http::HttpBuilder;
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::prefix::PrefixStore;
+use object_store::{
+    parse_url_opts, ClientConfigKey, ClientOptions, GetOptions, GetRange, ObjectStore,
+};
+use serde_json::json;
+use shared_crypto::intent::Intent;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fs, io::Write, path::PathBuf, str::FromStr};
+use sui_config::genesis::Genesis;
+use sui_json_rpc_types::{
+    Coin, EventFilter, EventPage, ObjectChange, SuiExecutionStatus, SuiObjectDataOptions,
+    SuiPastObjectResponse, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse,
+    SuiTransactionBlockResponseOptions,
+};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore};
+use sui_rest_api::CheckpointData;
+use sui_sdk::types::{
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    quorum_driver_types::ExecuteTransactionRequestType,
+    transaction::{
+        Command, ProgrammableMoveCall, ProgrammableTransaction, Transaction, TransactionData,
+        TransactionKind,
+    },
+};
+use sui_sdk::{SuiClient, SuiClientBuilder};
+use sui_types::transaction::{Argument, ObjectArg, TransactionDataAPI};
+use sui_types::{
+    base_types::{AuthorityName, ObjectID, ObjectRef, SequenceNumber, SuiAddress},
+    committee::Committee,
+    digests::{ChainIdentifier, TransactionDigest},
+    dynamic_field::derive_dynamic_field_id,
+    effects::{TransactionEffects, TransactionEffectsAPI, TransactionEvents},
+    event::EventID,
+    messages_checkpoint::CertifiedCheckpointSummary,
+    object::{Object, Owner},
+    sui_serde::BigInt,
+};
+use tokio::sync::OnceCell;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::bundle::ProofBundle;
+use crate::checkpoint_store::{read_legacy_file, CheckpointStore};
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::config::{CheckpointSource, Config, EpochLookup};
+use crate::error::LightClientError;
+use crate::gas_station::GasStationClient;
+use crate::limiter::RequestLimiter;
+use crate::metrics::Metrics;
+use crate::network_stub::NetworkStub;
+use crate::proof::{build_transaction_proof, TransactionProof};
+use crate::verifier::{
+    check_contiguous_epochs, check_signature_epoch, committee_from_stakes,
+    extract_verified_effects_and_events, extract_verified_sender_and_gas,
+    extract_verified_transaction_data, next_committee_from_summary,
+    next_protocol_version_from_summary, verify_checkpoint_summary, verify_checkpoint_transactions,
+    verify_end_of_epoch_summary, VerifiedSenderAndGas, VerifiedTransaction,
+};
+
+/// Loads the committee of the genesis epoch from the genesis file.
+///
+/// The genesis committee signs every checkpoint of epoch 0, including its end-of-epoch
+/// checkpoint, whose `next_epoch_committee` is the committee of epoch 1. The checkpoint
+/// list therefore starts at the last checkpoint of epoch 0. Networks whose genesis
+/// committee signs a later epoch set it as `genesis_committee_epoch`.
+pub fn genesis_committee(config: &Config) -> anyhow::Result<Committee> {
+    let mut genesis_path = config.checkpoint_summary_dir.clone();
+    genesis_path.push(&config.genesis_filename);
+    let genesis_committee = Genesis::load(&genesis_path)?.committee()?;
+    anyhow::ensure!(
+        genesis_committee.epoch == 0,
+        "Genesis committee is for epoch {}, expected epoch 0",
+        genesis_committee.epoch
+    );
+    if config.genesis_committee_epoch == 0 {
+        return Ok(genesis_committee);
+    }
+    Ok(committee_from_stakes(
+        config.genesis_committee_epoch,
+        genesis_committee.voting_rights,
+    )?)
+}
+
+/// Versions of the checkpoint blob format that can be decoded, currently only BCS.
+const SUPPORTED_CHECKPOINT_BLOB_VERSIONS: &[u8] = &[1];
+
+/// Splits a checkpoint blob into its leading version byte and the encoded checkpoint data,
+/// failing if the version is not supported.
+fn checkpoint_blob_data(bytes: &[u8]) -> anyhow::Result<&[u8]> {
+    let (version, data) = bytes
+        .split_first()
+        .ok_or(anyhow!("empty checkpoint blob"))?;
+    anyhow::ensure!(
+        SUPPORTED_CHECKPOINT_BLOB_VERSIONS.contains(version),
+        "unsupported checkpoint blob version {}",
+        version
+    );
+    Ok(data)
+}
+
+/// Decodes a checkpoint blob from the object store, a leading version byte followed by
+/// the encoded checkpoint data.
+pub fn decode_checkpoint_blob(bytes: &[u8]) -> anyhow::Result<CheckpointData> {
+    Ok(bcs::from_bytes(checkpoint_blob_data(bytes)?)?)
+}
+
+/// Bytes read from the start of a checkpoint blob to decode its summary without
+/// downloading the contents and transactions, enough for an end-of-epoch summary carrying
+/// the next committee.
+const SUMMARY_RANGE_BYTES: usize = 64 * 1024;
+
+/// Consumes the rest of a BCS input, to decode a value from a prefix of a longer one.
+struct Remainder;
+
+impl<'de> serde::Deserialize<'de> for Remainder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RemainderVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for RemainderVisitor {
+            type Value = Remainder;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("any remaining bytes")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Remainder, A::Error> {
+                // Reading past the end of the input fails, which ends the remainder
+                while let Ok(Some(_)) = seq.next_element::<u8>() {}
+                Ok(Remainder)
+            }
+        }
+
+        deserializer.deserialize_tuple(usize::MAX, RemainderVisitor)
+    }
+}
+
+/// Decodes the summary of a checkpoint blob from a prefix of it, failing if the prefix
+/// ends before the summary does.
+fn decode_checkpoint_summary_prefix(bytes: &[u8]) -> anyhow::Result<CertifiedCheckpointSummary> {
+    // The summary is the first field of the checkpoint data
+    let (summary, Remainder) = bcs::from_bytes(checkpoint_blob_data(bytes)?)?;
+    Ok(summary)
+}
+
+/// Fails unless `committee` is the committee of the epoch of checkpoint `summary`.
+fn ensure_committee_epoch(
+    committee: &Committee,
+    summary: &CertifiedCheckpointSummary,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        committee.epoch == summary.epoch(),
+        "Committee is for epoch {} but checkpoint {} is in epoch {}",
+        committee.epoch,
+        summary.sequence_number,
+        summary.epoch()
+    );
+    Ok(())
+}
+
+/// Gas budget of dWallet transactions whose cost cannot be estimated
+const DEFAULT_GAS_BUDGET: u64 = 1_000_000_000;
+
+/// How long the gas station holds the coins reserved for a submission.
+const GAS_RESERVATION_SECS: u64 = 60;
+
+/// How long to wait before retrying a download the object store rate limited.
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Most end-of-epoch checkpoints downloaded to verify a transaction past the stored ones
+const MAX_AUTO_FETCH_EPOCHS: u64 = 5;
+
+/// How often to check whether a pending transaction has been included in a checkpoint
+const TRANSACTION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Full node error message for a transaction digest it does not know
+const TRANSACTION_NOT_FOUND_MESSAGE: &str = "Could not find the referenced transaction";
+
+/// Whether a state proof event is about the dWallet registry `registry_id`, comparing the
+/// parsed ids so short and padded forms match.
+fn is_registry_event(json: &serde_json::Value, registry_id: ObjectID) -> bool {
+    json.get("registry_id")
+        .and_then(|id| id.as_str())
+        .and_then(|id| ObjectID::from_hex_literal(id).ok())
+        == Some(registry_id)
+}
+
+/// Whether an object store error was caused by a 429 Too Many Requests response.
+fn is_rate_limited(e: &object_store::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(e);
+    while let Some(err) = source {
+        if let Some(status) = err
+            .downcast_ref::<reqwest::Error>()
+            .and_then(|e| e.status())
+        {
+            return status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+        }
+        source = err.source();
+    }
+    // Client errors are reported with the status only in their message
+    e.to_string().contains("429 Too Many Requests")
+}
+
+/// Whether a full node REST request failed with a 404 Not Found response, e.g. for a
+/// checkpoint the node pruned or has not reached yet.
+fn is_rest_not_found(e: &anyhow::Error) -> bool {
+    e.chain().any(|err| {
+        err.downcast_ref::<reqwest::Error>()
+            .and_then(|e| e.status())
+            .is_some_and(|status| status == reqwest::StatusCode::NOT_FOUND)
+    }) || format!("{:#}", e).contains("404 Not Found")
+}
+
+/// Picks the keystore address signing dWallet transactions, `address` if given or
+/// else the first key in the keystore.
+pub fn select_sender(
+    keystore: &FileBasedKeystore,
+    address: Option<SuiAddress>,
+) -> anyhow::Result<SuiAddress> {
+    let sender = match address {
+        Some(address) => address,
+        None => {
+            *keystore
+                .addresses_with_alias()
+                .first()
+                .ok_or(anyhow!("The keystore has no keys"))?
+                .0
+        }
+    };
+    let key = keystore
+        .get_key(&sender)
+        .map_err(|_| anyhow!("Sender {} is not in the keystore", sender))?;
+    info!(%sender, scheme = %key.public().scheme(), "Using sender from keystore");
+    Ok(sender)
+}
+
+/// Estimates the gas budget of programmable transaction `pt` sent by `sender` by dev
+/// inspecting it, adding `margin_percent` on top of its computation and storage cost.
+///
+/// Falls back to a budget of 1 SUI if the transaction cannot be inspected.
+pub async fn estimate_gas_budget(
+    client: &SuiClient,
+    sender: SuiAddress,
+    pt: &ProgrammableTransaction,
+    gas_price: u64,
+    margin_percent: u64,
+) -> u64 {
+    let results = client
+        .read_api()
+        .dev_inspect_transaction_block(
+            sender,
+            TransactionKind::ProgrammableTransaction(pt.clone()),
+            Some(BigInt::from(gas_price)),
+            None,
+            None,
+        )
+        .await;
+    let error = match results {
+        Ok(results) if results.error.is_none() => {
+            let cost = results.effects.gas_cost_summary();
+            let estimate = cost.computation_cost + cost.storage_cost;
+            let budget = estimate + estimate * margin_percent / 100;
+            debug!(estimate, budget, "Estimated gas budget");
+            return budget;
+        }
+        Ok(results) => results.error.unwrap_or_default(),
+        Err(e) => e.to_string(),
+    };
+    warn!(
+        %error,
+        budget = DEFAULT_GAS_BUDGET,
+        "Gas estimation failed, using the default budget"
+    );
+    DEFAULT_GAS_BUDGET
+}
+
+/// Returns the reference of the first object created in `changes` whose type contains
+/// `type_substring`, e.g. `EpochCommittee`.
+pub fn find_created_object(
+    changes: &[ObjectChange],
+    type_substring: &str,
+) -> anyhow::Result<ObjectRef> {
+    changes
+        .iter()
+        .find(|change| match change {
+            ObjectChange::Created { object_type, .. } => {
+                object_type.to_string().contains(type_substring)
+            }
+            _ => false,
+        })
+        .map(|change| change.object_ref())
+        .ok_or_else(|| {
+            anyhow!(
+                "No object of a type containing {} was created",
+                type_substring
+            )
+        })
+}
+
+/// Selects the coin of `sender` with the highest balance to pay for gas, failing if
+/// it holds no coin with at least `min_balance`.
+pub async fn select_gas_coin(
+    client: &SuiClient,
+    sender: SuiAddress,
+    min_balance: u64,
+) -> anyhow::Result<Coin> {
+    let mut max_coin: Option<Coin> = None;
+    let mut cursor = None;
+    loop {
+        let page = client
+            .coin_read_api()
+            .get_coins(sender, None, cursor, None)
+            .await
+            .with_context(|| format!("fetching gas coins of {}", sender))?;
+        for coin in page.data {
+            if max_coin
+                .as_ref()
+                .map_or(true, |max| coin.balance > max.balance)
+            {
+                max_coin = Some(coin);
+            }
+        }
+
+        // Stop at the last page, or if the cursor does not advance
+        match page.next_cursor {
+            Some(next_cursor) if page.has_next_page && cursor.as_ref() != Some(&next_cursor) => {
+                cursor = Some(next_cursor)
+            }
+            _ => break,
+        }
+    }
+
+    match max_coin {
+        Some(coin) if coin.balance >= min_balance => Ok(coin),
+        Some(coin) => Err(anyhow!(
+            "No gas coin of {} covers the gas budget of {}, the largest has {}",
+            sender,
+            min_balance,
+            coin.balance
+        )),
+        None => Err(anyhow!("{} has no gas coins", sender)),
+    }
+}
+
+// The list of checkpoints at the end of each epoch
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct CheckpointsList {
+    // List of end of epoch checkpoints
+    pub checkpoints: Vec<u64>,
+}
+
+// The progress of the last sync of the dWallet network
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct SyncState {
+    // Highest end of epoch checkpoint that was verified and submitted
+    pub last_processed_checkpoint: Option<u64>,
+
+    /// Last checkpoint of the checkpoint list when it was last synced to the latest epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_listed_checkpoint: Option<u64>,
+
+    /// Epoch of the latest checkpoint at that sync, the first epoch missing from the list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_listed_epoch: Option<u64>,
+}
+
+impl SyncState {
+    /// Whether `list` is still complete when the latest checkpoint is in `latest_epoch`:
+    /// it ends with the checkpoint recorded by the last list sync and no epoch ended since.
+    pub fn is_list_current(&self, list: &CheckpointsList, latest_epoch: u64) -> bool {
+        self.last_listed_checkpoint.is_some()
+            && self.last_listed_checkpoint == list.checkpoints.last().copied()
+            && self.next_listed_epoch == Some(latest_epoch)
+    }
+}
+
+/// A GraphQL response, with `data` missing or partial when `errors` is not empty
+#[derive(Debug, serde::Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+/// `data` of the last checkpoint of epoch query, `epoch` is null for an unknown epoch
+#[derive(Debug, serde::Deserialize)]
+struct EpochLastCheckpointData {
+    epoch: Option<EpochCheckpoints>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct EpochCheckpoints {
+    checkpoints: CheckpointNodes,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct CheckpointNodes {
+    nodes: Vec<CheckpointNode>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckpointNode {
+    sequence_number: u64,
+}
+
+/// Parses the response to the last checkpoint of epoch query, including the raw `body`
+/// in the error if it does not have the expected shape.
+fn parse_last_checkpoint_of_epoch(epoch_id: u64, body: &str) -> anyhow::Result<u64> {
+    let response: GraphQlResponse<EpochLastCheckpointData> = serde_json::from_str(body)
+        .with_context(|| {
+            format!(
+                "Unexpected GraphQL response for epoch {}: {}",
+                epoch_id, body
+            )
+        })?;
+    if !response.errors.is_empty() {
+        let messages: Vec<_> = response.errors.iter().map(|e| e.message.as_str()).collect();
+        anyhow::bail!(
+            "GraphQL query for epoch {} failed: {}",
+            epoch_id,
+            messages.join("; ")
+        );
+    }
+    let epoch = response
+        .data
+        .ok_or_else(|| {
+            anyhow!(
+                "GraphQL response for epoch {} has no data: {}",
+                epoch_id,
+                body
+            )
+        })?
+        .epoch
+        .ok_or_else(|| anyhow!("Epoch {} not found", epoch_id))?;
+    epoch
+        .checkpoints
+        .nodes
+        .last()
+        .map(|node| node.sequence_number)
+        .ok_or_else(|| anyhow!("No checkpoints found for epoch {}", epoch_id))
+}
+
+/// Options of a checkpoint sync
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncOptions {
+    /// Build the first pending submission without executing it and stop there
+    pub dry_run: bool,
+    /// Show a progress bar while processing checkpoints, if stdout is a terminal
+    pub show_progress: bool,
+    /// On a failed download, process the checkpoints before it instead of none, and report
+    /// the failure at the end
+    pub continue_on_error: bool,
+}
+
+/// Outcome of [`LightClient::submit_transaction_proofs`]
+#[derive(Debug, Default)]
+pub struct ProofSubmission {
+    /// Digest of the dWallet transaction carrying the proofs, `None` in a dry run or if it
+    /// did not execute successfully
+    pub digest: Option<TransactionDigest>,
+    /// Transactions whose proof was submitted, or would be in a dry run
+    pub submitted: Vec<TransactionDigest>,
+    /// Transactions left out of the submission or whose submission failed, with the reason
+    pub failed: Vec<(TransactionDigest, anyhow::Error)>,
+}
+
+/// A transaction verified against the committee that signed its checkpoint, as returned by
+/// [`LightClient::get_verified_transaction`]
+#[derive(Debug, Clone)]
+pub struct VerifiedTransactionBlock {
+    /// The transaction data, with the inputs and commands of a programmable transaction
+    pub data: TransactionData,
+    pub effects: TransactionEffects,
+    pub events: Option<TransactionEvents>,
+    /// The committee the checkpoint including the transaction was verified against
+    pub committee: Committee,
+}
+
+/// The bytes of a checkpoint blob received so far, with its etag, for resuming the
+/// download after a failed attempt
+#[derive(Default)]
+struct PartialDownload {
+    bytes: Vec<u8>,
+    e_tag: Option<String>,
+    resumable: bool,
+}
+
+/// Writes a file through `write` into a temporary file next to it, renamed over `path`
+/// once complete, so an interrupted write never leaves `path` truncated.
+fn write_file_atomic(
+    path: &std::path::Path,
+    write: impl FnOnce(&mut fs::File) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file = fs::File::create(&tmp_path)?;
+    write(&mut file)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path).with_context(|| format!("Unable to replace {}", path.display()))
+}
+
+/// Builds the HTTP client for the GraphQL endpoint and the gas station, going through the
+/// proxies from the environment and trusting the configured extra root certificate.
+fn http_client(config: &Config) -> anyhow::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(config.request_timeout());
+    if let Some(proxy) = config.http_proxy() {
+        builder = builder.proxy(reqwest::Proxy::http(proxy)?);
+    }
+    if let Some(proxy) = config.https_proxy() {
+        builder = builder.proxy(reqwest::Proxy::https(proxy)?);
+    }
+    if let Some(pem) = config.extra_ca_cert()? {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&pem)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Opens the checkpoint object store at `url` with the backend of its url scheme and the
+/// configured options, with the same proxy and root certificate as [`http_client`] when it
+/// is served over http.
+fn open_object_store(config: &Config, url: &str) -> anyhow::Result<Box<dyn ObjectStore>> {
+    let url = Url::parse(url)?;
+    let proxy = match url.scheme() {
+        "http" => config.http_proxy(),
+        "https" => config.https_proxy(),
+        _ => {
+            // S3, GCS, Azure or local file store, configured by the options, with the
+            // checkpoints under the path of the url
+            let (object_store, store_path) = parse_url_opts(&url, &config.object_store_options)
+                .context("Invalid object store url or options")?;
+            if store_path.as_ref().is_empty() {
+                return Ok(object_store);
+            }
+            return Ok(Box::new(PrefixStore::new(object_store, store_path)));
+        }
+    };
+
+    let mut options = ClientOptions::new();
+    if let Some(proxy) = proxy {
+        options = options.with_proxy_url(proxy);
+    }
+    if let Some(pem) = config.extra_ca_cert()? {
+        options = options.with_root_certificate(object_store::Certificate::from_pem(&pem)?);
+    }
+    for (key, value) in &config.object_store_options {
+        let key: ClientConfigKey = key
+            .parse()
+            .with_context(|| format!("Invalid object store option {}", key))?;
+        options = options.with_config(key, value);
+    }
+    let object_store = HttpBuilder::new()
+        .with_url(url.as_str())
+        .with_client_options(options)
+        .build()?;
+    Ok(Box::new(object_store))
+}
+
+/// A light client handle owning the config and the connections used to verify
+/// checkpoints, transactions and objects.
+pub struct LightClient {
+    config: Config,
+    sui_client: OnceCell<SuiClient>,
+    dwallet_client: OnceCell<SuiClient>,
+    dwallet_breaker: CircuitBreaker,
+    registry_initial_shared_version: OnceCell<SequenceNumber>,
+    /// Set once the genesis file is checked against the targeted chain
+    genesis_checked: OnceCell<()>,
+    /// Set once the object store is found to respond, or from the start if it is not an
+    /// https mirror
+    object_store_checked: OnceCell<()>,
+    stop_requested: AtomicBool,
+    object_store: Box<dyn ObjectStore>,
+    /// The stores of `object_store_fallback_urls` with their url, in order
+    fallback_object_stores: Vec<(String, Box<dyn ObjectStore>)>,
+    object_store_limiter: RequestLimiter,
+    /// REST client of the full node, set if checkpoints are downloaded from it
+    rest_client: Option<sui_rest_api::Client>,
+    http_client: reqwest::Client,
+    gas_station: Option<GasStationClient>,
+    checkpoint_store: CheckpointStore,
+    metrics: Metrics,
+    /// Answers the full node and dWallet network requests instead, if set
+    network_stub: Option<Arc<dyn NetworkStub>>,
+    /// Replaces the committee read from the genesis file, if set
+    genesis_committee: Option<Committee>,
+}
+
+impl LightClient {
+    pub fn new(mut config: Config) -> anyhow::Result<Self> {
+        config.apply_network_defaults()?;
+        // Only left unset when checkpoints come from the full node, which never reads it
+        let object_store: Box<dyn ObjectStore> = if config.object_store_url.is_empty() {
+            Box::new(InMemory::new())
+        } else {
+            config.check_object_store_url()?;
+            open_object_store(&config, &config.object_store_url)?
+        };
+        let https_mirror = config.object_store_url.starts_with("https://")
+            && config.checkpoint_source == CheckpointSource::ObjectStore;
+        let mut client = Self::with_object_store(config, object_store)?;
+        if https_mirror {
+            client.object_store_checked = OnceCell::new();
+        }
+        Ok(client)
+    }
+
+    /// Creates a handle reading checkpoints from `object_store` instead of the one at
+    /// `object_store_url`, e.g. an in-memory store in tests.
+    pub fn with_object_store(
+        mut config: Config,
+        object_store: Box<dyn ObjectStore>,
+    ) -> anyhow::Result<Self> {
+        config.apply_network_defaults()?;
+        let http_client = http_client(&config)?;
+        let gas_station = config
+            .gas_station_url()
+            .map(|url| GasStationClient::new(&url, http_client.clone()));
+        let checkpoint_store = CheckpointStore::open(&config.checkpoint_summary_dir)?;
+        // An open breaker lets a trial request through once the retry budget elapsed
+        let dwallet_breaker = CircuitBreaker::new(
+            config.dwallet_breaker_threshold,
+            config.dwallet_retry_budget(),
+        );
+        let object_store_limiter =
+            RequestLimiter::new(config.object_store_max_concurrency, config.object_store_rps);
+        let fallback_object_stores = config
+            .object_store_fallback_urls
+            .iter()
+            .map(|url| {
+                let store = open_object_store(&config, url)
+                    .with_context(|| format!("Invalid fallback object store {}", url))?;
+                Ok((url.clone(), store))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let rest_client = (config.checkpoint_source == CheckpointSource::FullNode)
+            .then(|| sui_rest_api::Client::new(config.sui_rest_url()));
+        Ok(Self {
+            config,
+            checkpoint_store,
+            sui_client: OnceCell::new(),
+            dwallet_client: OnceCell::new(),
+            dwallet_breaker,
+            registry_initial_shared_version: OnceCell::new(),
+            genesis_checked: OnceCell::new(),
+            object_store_checked: OnceCell::new_with(Some(())),
+            stop_requested: AtomicBool::new(false),
+            object_store_limiter,
+            object_store,
+            fallback_object_stores,
+            rest_client,
+            http_client,
+            gas_station,
+            metrics: Metrics::new(),
+            network_stub: None,
+            genesis_committee: None,
+        })
+    }
+
+    /// Answers the lookups on the full node and the submissions to the dWallet network with
+    /// `stub`, e.g. to sync checkpoints from an in-memory object store in tests.
+    pub fn with_network_stub(mut self, stub: Arc<dyn NetworkStub>) -> Self {
+        self.network_stub = Some(stub);
+        self
+    }
+
+    /// Trusts `committee` as the genesis committee instead of reading the genesis file,
+    /// which is then not checked against the targeted chain.
+    pub fn with_genesis_committee(mut self, committee: Committee) -> Self {
+        self.genesis_committee = Some(committee);
+        self.genesis_checked = OnceCell::new_with(Some(()));
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Awaits `future` for at most the configured request timeout, failing with an
+    /// error naming `operation` if it takes longer.
+    pub async fn timed<F: Future>(&self, operation: &str, future: F) -> anyhow::Result<F::Output> {
+        let timeout = self.config.request_timeout();
+        tokio::time::timeout(timeout, future)
+            .await
+            .map_err(|_| anyhow!("{} timed out after {}s", operation, timeout.as_secs()))
+    }
+
+    /// Starts serving Prometheus metrics on `metrics_addr`, doing nothing if it is unset.
+    pub fn start_metrics_exporter(&self) -> Option<tokio::task::JoinHandle<()>> {
+        self.config
+            .metrics_addr
+            .map(|addr| self.metrics.start_exporter(addr))
+    }
+
+    /// Asks a running sync to stop once the checkpoint it is processing is verified,
+    /// submitted and written, e.g. on Ctrl-C.
+    pub fn request_stop(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::SeqCst)
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.config.offline
+    }
+
+    /// Fails in offline mode, before `operation` accesses the network.
+    fn ensure_online(&self, operation: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.config.offline,
+            "{} needs network access, which is disabled in offline mode",
+            operation
+        );
+        Ok(())
+    }
+
+    /// Client for the Sui full node, connected on first use
+    pub async fn sui_client(&self) -> anyhow::Result<&SuiClient> {
+        self.ensure_online("Connecting to the Sui full node")?;
+        self.sui_client
+            .get_or_try_init(|| async {
+                self.metrics.rpc_clients_built.inc();
+                let builder =
+                    SuiClientBuilder::default().request_timeout(self.config.request_timeout());
+                self.timed(
+                    "Connecting to the Sui full node",
+                    builder.build(self.config.sui_full_node_url.as_str()),
+                )
+                .await?
+                .context("building sui client")
+            })
+            .await
+    }
+
+    /// Client for the dWallet full node, connected on first use
+    pub async fn dwallet_client(&self) -> anyhow::Result<&SuiClient> {
+        self.ensure_online("Connecting to the dWallet full node")?;
+        self.dwallet_client
+            .get_or_try_init(|| async {
+                self.metrics.rpc_clients_built.inc();
+                let builder =
+                    SuiClientBuilder::default().request_timeout(self.config.request_timeout());
+                self.timed(
+                    "Connecting to the dWallet full node",
+                    builder.build(self.config.dwallet_full_node_url()),
+                )
+                .await?
+                .context("building dwallet client")
+            })
+            .await
+    }
+
+    /// Checks that the full node and the object store serve the same chain, by comparing
+    /// the chain identifier of the full node with the digest of checkpoint 0 in the object
+    /// store. Returns the chain identifier, or [`LightClientError::ChainMismatch`].
+    pub async fn check_chain_identifier(&self) -> anyhow::Result<String> {
+        let read_api = self.sui_client().await?.read_api();
+        let full_node = self
+            .timed(
+                "Fetching the chain identifier",
+                read_api.get_chain_identifier(),
+            )
+            .await??;
+        let genesis_checkpoint = self.download_checkpoint_summary(0).await?;
+        let object_store = ChainIdentifier::from(*genesis_checkpoint.digest()).to_string();
+        if full_node != object_store {
+            return Err(LightClientError::ChainMismatch {
+                full_node,
+                object_store,
+            }
+            .into());
+        }
+        Ok(full_node)
+    }
+
+    /// Checks that the genesis file is for the chain the light client targets, failing with
+    /// [`LightClientError::GenesisMismatch`] otherwise. Its genesis checkpoint must have
+    /// `genesis_digest` if set, belong to the chain of the `network` preset if any, and,
+    /// unless offline, be the genesis of the chain the full node serves.
+    ///
+    /// The check runs once per handle, a later call returns right away.
+    pub async fn check_genesis(&self) -> anyhow::Result<()> {
+        self.genesis_checked
+            .get_or_try_init(|| async {
+                let mut genesis_path = self.config.checkpoint_summary_dir.clone();
+                genesis_path.push(&self.config.genesis_filename);
+                let digest = *Genesis::load(&genesis_path)?.checkpoint().digest();
+                let chain = ChainIdentifier::from(digest).to_string();
+
+                if let Some(expected) = self.config.genesis_digest {
+                    if digest != expected {
+                        return Err(LightClientError::GenesisMismatch {
+                            genesis: digest.to_string(),
+                            expected: expected.to_string(),
+                            expected_by: "genesis_digest".to_string(),
+                        }
+                        .into());
+                    }
+                }
+                if let Some(network) = self.config.network {
+                    if let Some(expected) = network.chain_identifier() {
+                        if chain != expected {
+                            return Err(LightClientError::GenesisMismatch {
+                                genesis: chain,
+                                expected: expected.to_string(),
+                                expected_by: format!("the {} preset", network.name()),
+                            }
+                            .into());
+                        }
+                    }
+                }
+                if !self.is_offline() {
+                    let read_api = self.sui_client().await?.read_api();
+                    let full_node = self
+                        .timed(
+                            "Fetching the chain identifier",
+                            read_api.get_chain_identifier(),
+                        )
+                        .await??;
+                    if full_node != chain {
+                        return Err(LightClientError::GenesisMismatch {
+                            genesis: chain,
+                            expected: full_node,
+                            expected_by: "the full node".to_string(),
+                        }
+                        .into());
+                    }
+                }
+                debug!(%chain, "Genesis file matches the targeted chain");
+                Ok::<_, anyhow::Error>(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Checks once that an https object store mirror responds, by looking up checkpoint 0,
+    /// so a typo in `object_store_url` fails with a hint instead of a checkpoint missing
+    /// deep in a sync. A mirror without checkpoint 0 is accepted with a warning if fallback
+    /// stores may hold it.
+    pub async fn check_object_store(&self) -> anyhow::Result<()> {
+        self.object_store_checked
+            .get_or_try_init(|| async {
+                let url = &self.config.object_store_url;
+                let path = Path::from(self.config.checkpoint_object_path(0, Some(0))?);
+                let head = self
+                    .timed("Checking the object store", self.object_store.head(&path))
+                    .await?;
+                match head {
+                    Ok(_) => debug!(store = %url, "Object store responds"),
+                    Err(object_store::Error::NotFound { .. })
+                        if !self.fallback_object_stores.is_empty() =>
+                    {
+                        warn!(store = %url, "Object store has no checkpoint 0");
+                    }
+                    Err(object_store::Error::NotFound { .. }) => {
+                        return Err(anyhow!(
+                            "no checkpoint 0 in object store '{}' at {}, check object_store_url",
+                            url,
+                            path
+                        ));
+                    }
+                    Err(e) => {
+                        return Err(anyhow!(e).context(format!(
+                            "object store at '{}' is unreachable, check object_store_url",
+                            url
+                        )));
+                    }
+                }
+                Ok::<_, anyhow::Error>(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Runs `request` against the dWallet full node, retrying failures for at most
+    /// `dwallet_retry_budget_secs`. Once `dwallet_breaker_threshold` requests failed in a
+    /// row, fails fast with [`LightClientError::DwalletUnavailable`] instead.
+    pub async fn dwallet_request<'a, T, F, Fut>(
+        &'a self,
+        operation: &str,
+        request: F,
+    ) -> anyhow::Result<T>
+    where
+        F: Fn(&'a SuiClient) -> Fut,
+        Fut: Future<Output = sui_sdk::error::SuiRpcResult<T>>,
+    {
+        let unavailable = |state: CircuitState| match state {
+            CircuitState::Open {
+                consecutive_failures,
+            }
+            | CircuitState::Closed {
+                consecutive_failures,
+            } => LightClientError::DwalletUnavailable {
+                failures: consecutive_failures,
+            },
+        };
+        if !self.dwallet_breaker.allows_request() {
+            return Err(unavailable(self.dwallet_breaker.state()).into());
+        }
+
+        let backoff = ExponentialBackoff {
+            max_elapsed_time: Some(self.config.dwallet_retry_budget()),
+            ..Default::default()
+        };
+        backoff::future::retry(backoff, || async {
+            let result = match self.dwallet_client().await {
+                Ok(client) => self
+                    .timed(operation, request(client))
+                    .await
+                    .and_then(|res| res.map_err(anyhow::Error::from)),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(value) => {
+                    self.dwallet_breaker.record_success();
+                    Ok(value)
+                }
+                Err(e) if self.dwallet_breaker.record_failure() => {
+                    warn!(error = %e, "{} failed, dWallet circuit breaker open", operation);
+                    Err(backoff::Error::permanent(
+                        anyhow::Error::new(unavailable(self.dwallet_breaker.state()))
+                            .context(format!("{}: {:#}", operation, e)),
+                    ))
+                }
+                Err(e) => {
+                    warn!(error = %e, "{} failed, retrying", operation);
+                    Err(backoff::Error::transient(e))
+                }
+            }
+        })
+        .await
+    }
+
+    /// Whether dWallet full node requests go through, and how many failed in a row
+    pub fn dwallet_circuit_state(&self) -> CircuitState {
+        self.dwallet_breaker.state()
+    }
+
+    /// Number of full node and dWallet clients connected, at most one each per handle
+    pub fn rpc_clients_built(&self) -> u64 {
+        self.metrics.rpc_clients_built.get()
+    }
+
+    pub fn read_checkpoint_list(&self) -> anyhow::Result<CheckpointsList> {
+        let mut checkpoints_path = self.config.checkpoint_summary_dir.clone();
+        checkpoints_path.push("checkpoints.yaml");
+        // A fresh checkpoint directory starts with an empty list
+        if !checkpoints_path.exists() {
+            return Ok(CheckpointsList {
+                checkpoints: vec![],
+            });
+        }
+        // Read the resulting file and parse the yaml checkpoint list
+        let reader = fs::File::open(checkpoints_path.clone())?;
+        Ok(serde_yaml::from_reader(reader)?)
+    }
+
+    /// Whether checkpoint `seq` is stored, in the checkpoint store or as a legacy file.
+    pub fn has_checkpoint(&self, seq: u64) -> bool {
+        self.checkpoint_store.contains(seq) || self.legacy_checkpoint_path(seq).exists()
+    }
+
+    /// Sequence numbers of the checkpoints in the checkpoint store, in ascending order.
+    pub fn stored_checkpoints(&self) -> Vec<u64> {
+        self.checkpoint_store.sequence_numbers()
+    }
+
+    /// Sequence numbers of the end-of-epoch checkpoints in the checkpoint store, in
+    /// ascending order. The trust chain is the checkpoint list, which may hold fewer.
+    pub fn stored_end_of_epoch_checkpoints(&self) -> Vec<u64> {
+        self.checkpoint_store.end_of_epoch_sequence_numbers()
+    }
+
+    pub fn read_checkpoint(&self, seq: u64) -> anyhow::Result<CertifiedCheckpointSummary> {
+        let summary = match self.checkpoint_store.get(seq)? {
+            Some(summary) => summary,
+            None => {
+                // Fall back to the one file per checkpoint layout of earlier versions
+                let legacy_path = self.legacy_checkpoint_path(seq);
+                if !legacy_path.exists() {
+                    return Err(LightClientError::CheckpointNotFound(seq).into());
+                }
+                read_legacy_file(&legacy_path)?
+            }
+        };
+        check_signature_epoch(&summary)?;
+        Ok(summary)
+    }
+
+    pub fn write_checkpoint(&self, summary: &CertifiedCheckpointSummary) -> anyhow::Result<()> {
+        self.checkpoint_store.insert(summary)
+    }
+
+    /// Imports checkpoints stored as `<seq>.yaml` files by earlier versions into the
+    /// checkpoint store, returning how many were added.
+    pub fn migrate_legacy_checkpoints(&self) -> anyhow::Result<usize> {
+        self.checkpoint_store.import_legacy_files()
+    }
+
+    fn legacy_checkpoint_path(&self, seq: u64) -> PathBuf {
+        let mut checkpoint_path = self.config.checkpoint_summary_dir.clone();
+        checkpoint_path.push(format!("{}.yaml", seq));
+        checkpoint_path
+    }
+
+    pub fn write_checkpoint_list(&self, checkpoints_list: &CheckpointsList) -> anyhow::Result<()> {
+        // Write the checkpoint list to a file
+        let mut checkpoints_path = self.config.checkpoint_summary_dir.clone();
+        checkpoints_path.push("checkpoints.yaml");
+        write_file_atomic(&checkpoints_path, |file| {
+            serde_yaml::to_writer(file, checkpoints_list)
+                .context("Unable to serialize checkpoint list")
+        })
+    }
+
+    pub fn read_sync_state(&self) -> anyhow::Result<SyncState> {
+        let mut sync_state_path = self.config.checkpoint_summary_dir.clone();
+        sync_state_path.push("sync_state.yaml");
+        // Nothing was processed before the first sync
+        if !sync_state_path.exists() {
+            return Ok(SyncState::default());
+        }
+        let reader = fs::File::open(sync_state_path)?;
+        Ok(serde_yaml::from_reader(reader)?)
+    }
+
+    pub fn write_sync_state(&self, sync_state: &SyncState) -> anyhow::Result<()> {
+        // Write the sync progress to a file
+        let mut sync_state_path = self.config.checkpoint_summary_dir.clone();
+        sync_state_path.push("sync_state.yaml");
+        write_file_atomic(&sync_state_path, |file| {
+            serde_yaml::to_writer(file, sync_state).context("Unable to serialize sync state")
+        })
+    }
+
+    pub fn read_committee(&self, epoch: u64) -> anyhow::Result<Committee> {
+        // Read the cached committee of the epoch
+        let mut committee_path = self.config.checkpoint_summary_dir.clone();
+        committee_path.push("committees");
+        committee_path.push(format!("{}.bcs", epoch));
+        let bytes =
+            fs::read(&committee_path).map_err(|_| LightClientError::CommitteeMissing { epoch })?;
+        let committee: Committee =
+            bcs::from_bytes(&bytes).map_err(|_| anyhow!("Unable to parse committee file"))?;
+        Ok(committee_from_stakes(
+            committee.epoch,
+            committee.voting_rights,
+        )?)
+    }
+
+    pub fn write_committee(&self, committee: &Committee) -> anyhow::Result<()> {
+        // Write the committee to a file keyed by its epoch
+        let mut committee_path = self.config.checkpoint_summary_dir.clone();
+        committee_path.push("committees");
+        fs::create_dir_all(&committee_path)?;
+        committee_path.push(format!("{}.bcs", committee.epoch));
+        let bytes =
+            bcs::to_bytes(committee).map_err(|_| anyhow!("Unable to serialize committee"))?;
+        write_file_atomic(&committee_path, |file| Ok(file.write_all(&bytes)?))
+    }
+
+    /// The protocol version of each epoch, as announced by the verified end-of-epoch
+    /// checkpoint before it. The last entry is the version of the newest verified epoch.
+    pub fn read_protocol_versions(&self) -> anyhow::Result<BTreeMap<u64, u64>> {
+        let mut versions_path = self.config.checkpoint_summary_dir.clone();
+        versions_path.push("committees");
+        versions_path.push("protocol_versions.yaml");
+        // No version is known before the first sync
+        if !versions_path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let reader = fs::File::open(versions_path)?;
+        Ok(serde_yaml::from_reader(reader)?)
+    }
+
+    pub fn write_protocol_versions(&self, versions: &BTreeMap<u64, u64>) -> anyhow::Result<()> {
+        // Write the versions next to the committees of the same epochs
+        let mut versions_path = self.config.checkpoint_summary_dir.clone();
+        versions_path.push("committees");
+        fs::create_dir_all(&versions_path)?;
+        versions_path.push("protocol_versions.yaml");
+        write_file_atomic(&versions_path, |file| {
+            serde_yaml::to_writer(file, versions).context("Unable to serialize protocol versions")
+        })
+    }
+
+    pub fn load_genesis_committee(&self) -> anyhow::Result<Committee> {
+        if let Some(committee) = &self.genesis_committee {
+            return Ok(committee.clone());
+        }
+        genesis_committee(&self.config)
+    }
+
+    /// The network address of each genesis validator. Checkpoints only record the names
+    /// and stakes of later committees, so validators that joined after genesis have none.
+    pub fn genesis_validator_addresses(&self) -> anyhow::Result<BTreeMap<AuthorityName, String>> {
+        let mut genesis_path = self.config.checkpoint_summary_dir.clone();
+        genesis_path.push(&self.config.genesis_filename);
+        Ok(Genesis::load(&genesis_path)?
+            .validator_set_for_tooling()
+            .iter()
+            .map(|validator| {
+                let metadata = validator.verified_metadata();
+                (
+                    metadata.sui_pubkey_bytes(),
+                    metadata.net_address.to_string(),
+                )
+            })
+            .collect())
+    }
+
+    /// Downloads and decodes the checkpoint blob `checkpoint_number` from the object store,
+    /// or from the first fallback store that has it if the object store does not. With the
+    /// `full_node` checkpoint source, downloads it from the full node REST API instead.
+    ///
+    /// Missing checkpoints move on to the next store immediately, rate limited requests back
+    /// off for longer and other store errors are retried. A blob that fails to decode is
+    /// never retried.
+    async fn fetch_checkpoint_data(
+        &self,
+        checkpoint_number: u64,
+        epoch: Option<u64>,
+    ) -> anyhow::Result<CheckpointData> {
+        self.ensure_online(&format!("Downloading checkpoint {}", checkpoint_number))?;
+        if let Some(rest_client) = &self.rest_client {
+            return self
+                .fetch_full_node_checkpoint(rest_client, checkpoint_number)
+                .await;
+        }
+        self.check_object_store().await?;
+        let path = Path::from(
+            self.config
+                .checkpoint_object_path(checkpoint_number, epoch)
+                .context("Invalid object_store_path_template")?,
+        );
+
+        let stores = std::iter::once((&self.config.object_store_url, &self.object_store)).chain(
+            self.fallback_object_stores
+                .iter()
+                .map(|(url, store)| (url, store)),
+        );
+        for (i, (url, store)) in stores.enumerate() {
+            match self
+                .fetch_blob(store.as_ref(), &path, checkpoint_number)
+                .await
+            {
+                Ok(bytes) => {
+                    if i == 0 {
+                        debug!(checkpoint = checkpoint_number, store = %url, "Checkpoint served");
+                    } else {
+                        info!(
+                            checkpoint = checkpoint_number,
+                            store = %url,
+                            "Checkpoint served by a fallback object store"
+                        );
+                    }
+                    return decode_checkpoint_blob(&bytes).with_context(|| {
+                        format!("Malformed checkpoint blob {}", checkpoint_number)
+                    });
+                }
+                Err(e)
+                    if matches!(
+                        e.downcast_ref(),
+                        Some(LightClientError::CheckpointNotFound(_))
+                    ) =>
+                {
+                    debug!(checkpoint = checkpoint_number, store = %url, "Checkpoint not found");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(LightClientError::CheckpointNotFound(checkpoint_number).into())
+    }
+
+    /// Downloads the full checkpoint `checkpoint_number` from the full node REST API,
+    /// retrying failed requests except for checkpoints the node does not have.
+    async fn fetch_full_node_checkpoint(
+        &self,
+        rest_client: &sui_rest_api::Client,
+        checkpoint_number: u64,
+    ) -> anyhow::Result<CheckpointData> {
+        let backoff = ExponentialBackoff {
+            max_elapsed_time: Some(Duration::from_secs(120)),
+            ..Default::default()
+        };
+        let checkpoint = backoff::future::retry(backoff, || async {
+            let result = self
+                .timed(
+                    &format!("Downloading checkpoint {}", checkpoint_number),
+                    rest_client.get_full_checkpoint(checkpoint_number),
+                )
+                .await
+                .and_then(|result| result.map_err(anyhow::Error::from));
+            result.map_err(|e| {
+                if is_rest_not_found(&e) {
+                    return backoff::Error::permanent(anyhow!(
+                        LightClientError::CheckpointNotFound(checkpoint_number)
+                    ));
+                }
+                warn!(
+                    checkpoint = checkpoint_number,
+                    error = %e,
+                    "Checkpoint download from the full node failed, retrying"
+                );
+                backoff::Error::transient(e)
+            })
+        })
+        .await?;
+        debug!(
+            checkpoint = checkpoint_number,
+            full_node = %self.config.sui_rest_url(),
+            "Checkpoint served"
+        );
+        Ok(checkpoint)
+    }
+
+    /// Downloads the blob of checkpoint `checkpoint_number` at `path` in `store`, retrying
+    /// as described in [`Self::fetch_checkpoint_data`].
+    ///
+    /// The bytes received by a failed attempt are kept and the next attempt requests the
+    /// rest of the blob only, with a range request conditional on its etag. If the store
+    /// does not serve the range, the download restarts from the start without resuming.
+    async fn fetch_blob(
+        &self,
+        store: &dyn ObjectStore,
+        path: &Path,
+        checkpoint_number: u64,
+    ) -> anyhow::Result<bytes::Bytes> {
+        let backoff = ExponentialBackoff {
+            max_elapsed_time: Some(Duration::from_secs(120)),
+            ..Default::default()
+        };
+        let partial = Mutex::new(PartialDownload {
+            resumable: true,
+            ..Default::default()
+        });
+        backoff::future::retry(backoff, || async {
+            let _permit = self.object_store_limiter.acquire().await;
+            let result = self
+                .timed(
+                    &format!("Downloading checkpoint {}", checkpoint_number),
+                    self.fetch_blob_attempt(store, path, checkpoint_number, &partial),
+                )
+                .await
+                .map_err(|e| {
+                    warn!(checkpoint = checkpoint_number, error = %e, "Checkpoint download timed out, retrying");
+                    self.metrics.object_store_retries.inc();
+                    backoff::Error::transient(e)
+                })?;
+            result.map_err(|e| match e {
+                object_store::Error::NotFound { .. } => backoff::Error::permanent(
+                    anyhow!(LightClientError::CheckpointNotFound(checkpoint_number)),
+                ),
+                e if is_rate_limited(&e) => {
+                    warn!(
+                        checkpoint = checkpoint_number,
+                        "Rate limited by the object store, backing off"
+                    );
+                    self.metrics.object_store_retries.inc();
+                    self.metrics.object_store_rate_limited.inc();
+                    // The object store does not expose the Retry-After header
+                    backoff::Error::retry_after(
+                        anyhow!(LightClientError::ObjectStore(e)),
+                        RATE_LIMIT_BACKOFF,
+                    )
+                }
+                e => {
+                    warn!(
+                        checkpoint = checkpoint_number,
+                        error = %e,
+                        received = partial.lock().unwrap().bytes.len(),
+                        "Checkpoint download failed, retrying"
+                    );
+                    self.metrics.object_store_retries.inc();
+                    backoff::Error::transient(anyhow!(LightClientError::ObjectStore(e)))
+                }
+            })
+        })
+        .await?;
+        Ok(std::mem::take(&mut partial.lock().unwrap().bytes).into())
+    }
+
+    /// Requests the part of the blob at `path` not in `partial` yet, and appends the bytes
+    /// to it as they arrive, so that they are kept if the connection drops.
+    async fn fetch_blob_attempt(
+        &self,
+        store: &dyn ObjectStore,
+        path: &Path,
+        checkpoint_number: u64,
+        partial: &Mutex<PartialDownload>,
+    ) -> object_store::Result<()> {
+        let (offset, options) = {
+            let mut partial = partial.lock().unwrap();
+            if !partial.resumable {
+                partial.bytes.clear();
+            }
+            let offset = partial.bytes.len();
+            let options = match offset {
+                0 => GetOptions::default(),
+                _ => GetOptions {
+                    range: Some(GetRange::Offset(offset)),
+                    if_match: partial.e_tag.clone(),
+                    ..Default::default()
+                },
+            };
+            (offset, options)
+        };
+        if offset > 0 {
+            info!(
+                checkpoint = checkpoint_number,
+                offset, "Resuming checkpoint download"
+            );
+        }
+
+        let result = match store.get_opts(path, options).await {
+            Ok(result) => result,
+            Err(e) => {
+                // The blob changed or the store rejects ranges, start over next time
+                if offset > 0 && !matches!(e, object_store::Error::NotFound { .. }) {
+                    partial.lock().unwrap().resumable = false;
+                }
+                return Err(e);
+            }
+        };
+        {
+            let mut partial = partial.lock().unwrap();
+            if result.range.start != partial.bytes.len() {
+                // The store sent the whole blob instead of the requested range
+                partial.bytes.clear();
+                partial.resumable = result.range.start == 0;
+            }
+            partial.e_tag = result.meta.e_tag.clone();
+        }
+        if result.range.start != 0 && result.range.start != offset {
+            return Err(object_store::Error::Generic {
+                store: "checkpoint download",
+                source: format!(
+                    "requested bytes from {} but received bytes from {}",
+                    offset, result.range.start
+                )
+                .into(),
+            });
+        }
+
+        let mut stream = result.into_stream();
+        while let Some(chunk) = stream.next().await {
+            partial.lock().unwrap().bytes.extend_from_slice(&chunk?);
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn download_checkpoint_summary(
+        &self,
+        checkpoint_number: u64,
+    ) -> anyhow::Result<CertifiedCheckpointSummary> {
+        self.download_summary(checkpoint_number, None).await
+    }
+
+    /// Downloads the summary of checkpoint `checkpoint_number`, in `epoch` if known for
+    /// object store paths that include it.
+    async fn download_summary(
+        &self,
+        checkpoint_number: u64,
+        epoch: Option<u64>,
+    ) -> anyhow::Result<CertifiedCheckpointSummary> {
+        let _timer = self.metrics.start_timer("download_checkpoint_summary");
+
+        // Read only the start of the blob if the object store serves ranges, else
+        // download the whole checkpoint from the server. The full node REST API serves
+        // whole checkpoints only
+        let range_read = match self.rest_client {
+            Some(_) => None,
+            None => Some(
+                self.fetch_checkpoint_summary(checkpoint_number, epoch)
+                    .await,
+            ),
+        };
+        let summary = match range_read {
+            Some(Ok(summary)) => summary,
+            range_read => {
+                if let Some(Err(e)) = range_read {
+                    debug!(
+                        checkpoint = checkpoint_number,
+                        error = %e,
+                        "Range read failed, downloading the full checkpoint"
+                    );
+                }
+                self.fetch_checkpoint_data(checkpoint_number, epoch)
+                    .await?
+                    .checkpoint_summary
+            }
+        };
+        self.metrics.checkpoints_downloaded.inc();
+
+        debug!(
+            epoch = summary.epoch(),
+            digest = %summary.digest(),
+            "Downloaded checkpoint summary"
+        );
+        Ok(summary)
+    }
+
+    /// Reads the summary of checkpoint `checkpoint_number` from the first
+    /// [`SUMMARY_RANGE_BYTES`] of its blob, without retrying.
+    ///
+    /// Single transactions cannot be read the same way, the blob has no index of where
+    /// each transaction starts, so verifying one still downloads the full checkpoint.
+    async fn fetch_checkpoint_summary(
+        &self,
+        checkpoint_number: u64,
+        epoch: Option<u64>,
+    ) -> anyhow::Result<CertifiedCheckpointSummary> {
+        self.ensure_online(&format!("Downloading checkpoint {}", checkpoint_number))?;
+        self.check_object_store().await?;
+        let path = Path::from(
+            self.config
+                .checkpoint_object_path(checkpoint_number, epoch)
+                .context("Invalid object_store_path_template")?,
+        );
+        let _permit = self.object_store_limiter.acquire().await;
+        let bytes = self
+            .timed(
+                &format!("Downloading checkpoint {} summary", checkpoint_number),
+                self.object_store.get_range(&path, 0..SUMMARY_RANGE_BYTES),
+            )
+            .await??;
+        decode_checkpoint_summary_prefix(&bytes)
+            .with_context(|| format!("Malformed checkpoint blob {}", checkpoint_number))
+    }
+
+    /// Downloads the full checkpoint `checkpoint_number`, or reads it from
+    /// `<checkpoint_number>.chk` in the checkpoint directory in offline mode.
+    pub async fn get_full_checkpoint(
+        &self,
+        checkpoint_number: u64,
+    ) -> anyhow::Result<CheckpointData> {
+        let _timer = self.metrics.start_timer("get_full_checkpoint");
+        if self.config.offline {
+            let mut checkpoint_path = self.config.checkpoint_summary_dir.clone();
+            checkpoint_path.push(format!("{}.chk", checkpoint_number));
+            let bytes = fs::read(&checkpoint_path).with_context(|| {
+                format!(
+                    "Unable to read full checkpoint {}",
+                    checkpoint_path.display()
+                )
+            })?;
+            return decode_checkpoint_blob(&bytes)
+                .with_context(|| format!("Malformed checkpoint blob {}", checkpoint_number));
+        }
+
+        // Download the full checkpoint from the server
+        let full_checkpoint = self.fetch_checkpoint_data(checkpoint_number, None).await?;
+        self.metrics.checkpoints_downloaded.inc();
+
+        debug!(checkpoint = checkpoint_number, "Downloaded full checkpoint");
+        Ok(full_checkpoint)
+    }
+
+    pub async fn query_last_checkpoint_of_epoch(&self, epoch_id: u64) -> anyhow::Result<u64> {
+        self.ensure_online(&format!(
+            "Looking up the last checkpoint of epoch {}",
+            epoch_id
+        ))?;
+
+        // GraphQL query to get the last checkpoint of an epoch
+        let query = json!({
+            "query": "query ($epochID: Int) { epoch(id: $epochID) { checkpoints(last: 1) { nodes { sequenceNumber } } } }",
+            "variables": { "epochID": epoch_id }
+        });
+
+        // Submit the query by POSTing to the GraphQL endpoint, retrying on
+        // connection and server errors
+        let backoff = ExponentialBackoff {
+            max_elapsed_time: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let body = backoff::future::retry(backoff, || async {
+            let response = self
+                .http_client
+                .post(&self.config.graphql_url)
+                .header("Content-Type", "application/json")
+                .body(query.to_string())
+                .send()
+                .await
+                .map_err(|e| {
+                    warn!(epoch = epoch_id, error = %e, "GraphQL request failed, retrying");
+                    if e.is_timeout() {
+                        backoff::Error::transient(anyhow!(
+                            "GraphQL query for epoch {} timed out after {}s",
+                            epoch_id,
+                            self.config.request_timeout().as_secs()
+                        ))
+                    } else {
+                        backoff::Error::transient(anyhow!("Cannot connect to graphql: {}", e))
+                    }
+                })?;
+
+            let status = response.status();
+            if status.is_server_error() {
+                warn!(epoch = epoch_id, %status, "GraphQL server error, retrying");
+                return Err(backoff::Error::transient(anyhow!(
+                    "GraphQL endpoint returned {}",
+                    status
+                )));
+            }
+            if !status.is_success() {
+                return Err(backoff::Error::permanent(anyhow!(
+                    "GraphQL endpoint returned {}",
+                    status
+                )));
+            }
+
+            response.text().await.map_err(|e| {
+                backoff::Error::transient(anyhow!("Cannot read GraphQL response: {}", e))
+            })
+        })
+        .await?;
+
+        parse_last_checkpoint_of_epoch(epoch_id, &body)
+    }
+
+    /// Finds the last checkpoint of an ended epoch by a binary search over the checkpoint
+    /// summaries up to the latest checkpoint, for networks without a GraphQL endpoint.
+    pub async fn search_last_checkpoint_of_epoch(&self, epoch_id: u64) -> anyhow::Result<u64> {
+        let latest_seq = self.latest_checkpoint_sequence_number().await?;
+        let latest = self.download_checkpoint_summary(latest_seq).await?;
+        anyhow::ensure!(
+            latest.epoch() > epoch_id,
+            "Epoch {} has not ended, the latest checkpoint {} is in epoch {}",
+            epoch_id,
+            latest_seq,
+            latest.epoch()
+        );
+
+        // Checkpoint `low` is at most in the epoch and checkpoint `high` is past it
+        let (mut low, mut high) = (0, latest_seq);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            let summary = self.download_checkpoint_summary(mid).await?;
+            if summary.epoch() <= epoch_id {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        debug!(
+            epoch = epoch_id,
+            checkpoint = low,
+            "Found last checkpoint of epoch by binary search"
+        );
+        Ok(low)
+    }
+
+    /// Looks up the last checkpoint of an epoch with the configured `epoch_lookup`.
+    async fn last_checkpoint_of_epoch(&self, epoch_id: u64) -> anyhow::Result<u64> {
+        match self.config.epoch_lookup {
+            EpochLookup::Graphql => self.query_last_checkpoint_of_epoch(epoch_id).await,
+            EpochLookup::BinarySearch => self.search_last_checkpoint_of_epoch(epoch_id).await,
+        }
+    }
+
+    /// Looks up the end-of-epoch checkpoint of `epoch` with the configured `epoch_lookup` and
+    /// verifies it against the committee chain from genesis. The end-of-epoch checkpoints
+    /// the stored list lacks before it are downloaded and verified on the way.
+    pub async fn verified_end_of_epoch_summary(
+        &self,
+        epoch: u64,
+    ) -> anyhow::Result<CertifiedCheckpointSummary> {
+        self.check_genesis().await?;
+        let mut committee = self.load_genesis_committee()?;
+        anyhow::ensure!(
+            committee.epoch <= epoch,
+            "Epoch {} is before the genesis committee of epoch {}",
+            epoch,
+            committee.epoch
+        );
+        for ckp_id in self.read_checkpoint_list()?.checkpoints {
+            if committee.epoch >= epoch {
+                break;
+            }
+            let summary = self.read_checkpoint(ckp_id).with_context(|| {
+                format!("Stored checkpoint {} is missing. Need to Sync.", ckp_id)
+            })?;
+            committee = self.verify_summary(&summary, &committee)?;
+        }
+        while committee.epoch < epoch {
+            let summary = self.download_end_of_epoch_summary(committee.epoch).await?;
+            committee = self
+                .verify_summary(&summary, &committee)
+                .with_context(|| format!("Committee chain broken at epoch {}", committee.epoch))?;
+        }
+
+        let summary = self.download_end_of_epoch_summary(epoch).await?;
+        self.verify_summary(&summary, &committee)
+            .with_context(|| format!("End of epoch {} checkpoint does not verify", epoch))?;
+        Ok(summary)
+    }
+
+    /// Looks up and downloads the end-of-epoch checkpoint summary of an epoch.
+    async fn download_end_of_epoch_summary(
+        &self,
+        epoch_id: u64,
+    ) -> anyhow::Result<CertifiedCheckpointSummary> {
+        let checkpoint_number = self.last_checkpoint_of_epoch(epoch_id).await?;
+        let summary = self
+            .download_summary(checkpoint_number, Some(epoch_id))
+            .await?;
+        anyhow::ensure!(
+            summary.epoch() == epoch_id && summary.end_of_epoch_data.is_some(),
+            "Checkpoint {} found for epoch {} is not its end of epoch checkpoint",
+            checkpoint_number,
+            epoch_id
+        );
+        Ok(summary)
+    }
+
+    /// Looks up each end of epoch checkpoint that is missing between the latest on the
+    /// list and the latest checkpoint, with GraphQL or a binary search per `epoch_lookup`.
+    #[tracing::instrument(skip(self))]
+    pub async fn sync_checkpoint_list_to_latest(&self) -> anyhow::Result<()> {
+        // Get the local checkpoint list
+        let mut checkpoints_list: CheckpointsList = self.read_checkpoint_list()?;
+        match checkpoints_list.checkpoints.last() {
+            Some(latest_in_list) => info!(checkpoint = latest_in_list, "Latest in list"),
+            None => info!("Empty checkpoint list, seeding from genesis"),
+        }
+
+        // Stop right away if no epoch ended since the list was last synced, which only
+        // takes the epoch of the latest checkpoint from the full node
+        let sync_state = self.read_sync_state()?;
+        if sync_state.last_listed_checkpoint.is_some() {
+            let latest_epoch = self.latest_checkpoint_epoch().await?;
+            if sync_state.is_list_current(&checkpoints_list, latest_epoch) {
+                info!(
+                    epoch = latest_epoch,
+                    "Checkpoint list already at the latest epoch"
+                );
+                return Ok(());
+            }
+        }
+
+        // Verify the list up to its latest checkpoint to get the current committee.
+        // An empty list leaves the genesis committee, so syncing starts at the first
+        // epoch it signs.
+        let mut prev_committee = self.load_genesis_committee()?;
+        let mut verified_checkpoints = vec![];
+        for ckp_id in &checkpoints_list.checkpoints {
+            let summary = if self.has_checkpoint(*ckp_id) {
+                self.read_checkpoint(*ckp_id)?
+            } else {
+                self.download_checkpoint_summary(*ckp_id)
+                    .await
+                    .context("Failed to download checkpoint")?
+            };
+
+            // Fill in end of epoch checkpoints missing before this one, e.g. lists
+            // that start after epoch 0
+            while prev_committee.epoch < summary.epoch() {
+                let missing = self
+                    .download_end_of_epoch_summary(prev_committee.epoch)
+                    .await?;
+                prev_committee = self.verify_summary(&missing, &prev_committee)?;
+                info!(
+                    epoch = missing.epoch(),
+                    checkpoint = missing.sequence_number,
+                    "Filled missing end of epoch checkpoint"
+                );
+                verified_checkpoints.push(missing.sequence_number);
+            }
+
+            prev_committee = self.verify_summary(&summary, &prev_committee)?;
+            verified_checkpoints.push(*ckp_id);
+        }
+        checkpoints_list.checkpoints = verified_checkpoints;
+        let first_missing_epoch = prev_committee.epoch;
+
+        // Download the very latest checkpoint
+        let latest_seq = self.latest_checkpoint_sequence_number().await?;
+        let latest = self.download_checkpoint_summary(latest_seq).await?;
+        info!(
+            epoch = latest.epoch(),
+            checkpoint = latest_seq,
+            "Downloaded latest checkpoint"
+        );
+
+        // Concurrently download the end of epoch checkpoints of all the missing epochs
+        let missing_epochs = first_missing_epoch..latest.epoch();
+        let mut results = stream::iter(missing_epochs)
+            .map(|epoch| async move { (epoch, self.download_end_of_epoch_summary(epoch).await) })
+            .buffer_unordered(self.config.sync_concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+        results.sort_by_key(|(epoch, _)| *epoch);
+
+        // Verify and record the missing end of epoch checkpoints numbers in order,
+        // up to the first failed download
+        let mut next_epoch = first_missing_epoch;
+        for (epoch, result) in results {
+            let summary = match result {
+                Ok(summary) => summary,
+                Err(e) => {
+                    warn!(
+                        epoch,
+                        error = ?e,
+                        "Download failed, falling back to sequential sync"
+                    );
+                    break;
+                }
+            };
+
+            prev_committee = match self.verify_summary(&summary, &prev_committee) {
+                Ok(committee) => committee,
+                Err(e) => {
+                    self.write_checkpoint_list(&checkpoints_list)?;
+                    return Err(e.context(format!("Refusing to record epoch {}", epoch)));
+                }
+            };
+            checkpoints_list.checkpoints.push(summary.sequence_number);
+            next_epoch = epoch + 1;
+
+            info!(
+                epoch,
+                checkpoint = summary.sequence_number,
+                "Recorded end of epoch checkpoint"
+            );
+        }
+        self.write_checkpoint_list(&checkpoints_list)?;
+
+        // Sequentially verify and record the rest, saving the list after each epoch
+        while next_epoch < latest.epoch() {
+            let summary = self.download_end_of_epoch_summary(next_epoch).await?;
+            prev_committee = self
+                .verify_summary(&summary, &prev_committee)
+                .with_context(|| format!("Refusing to record epoch {}", next_epoch))?;
+
+            // Add to the list
+            checkpoints_list.checkpoints.push(summary.sequence_number);
+            self.write_checkpoint_list(&checkpoints_list)?;
+
+            info!(
+                epoch = next_epoch,
+                checkpoint = summary.sequence_number,
+                "Recorded end of epoch checkpoint"
+            );
+
+            // Update
+            next_epoch += 1;
+        }
+
+        self.write_sync_state(&SyncState {
+            last_listed_checkpoint: checkpoints_list.checkpoints.last().copied(),
+            next_listed_epoch: Some(latest.epoch()),
+            ..self.read_sync_state()?
+        })?;
+        Ok(())
+    }
+
+    /// The sequence number of the latest checkpoint, as reported by the full node.
+    async fn latest_checkpoint_sequence_number(&self) -> anyhow::Result<u64> {
+        if let Some(stub) = &self.network_stub {
+            return stub.latest_checkpoint().await;
+        }
+        let read_api = self.sui_client().await?.read_api();
+        Ok(self
+            .timed(
+                "Querying the latest checkpoint",
+                read_api.get_latest_checkpoint_sequence_number(),
+            )
+            .await??)
+    }
+
+    /// The epoch of the latest checkpoint, as reported by the full node in a single
+    /// request without downloading the checkpoint.
+    async fn latest_checkpoint_epoch(&self) -> anyhow::Result<u64> {
+        if self.network_stub.is_some() {
+            let latest_seq = self.latest_checkpoint_sequence_number().await?;
+            return Ok(self.download_checkpoint_summary(latest_seq).await?.epoch());
+        }
+        let read_api = self.sui_client().await?.read_api();
+        let page = self
+            .timed(
+                "Querying the latest checkpoint",
+                read_api.get_checkpoints(None, Some(1), true),
+            )
+            .await??;
+        let latest = page
+            .data
+            .first()
+            .ok_or_else(|| anyhow!("Full node returned no checkpoint"))?;
+        debug!(
+            epoch = latest.epoch,
+            checkpoint = latest.sequence_number,
+            "Latest checkpoint"
+        );
+        Ok(latest.epoch)
+    }
+
+    /// Syncs and verifies the end-of-epoch checkpoints, submitting the committees not yet
+    /// registered to the dWallet network.
+    #[tracing::instrument(skip(self))]
+    pub async fn check_and_sync_checkpoints(&self, options: SyncOptions) -> anyhow::Result<()> {
+        self.check_genesis().await?;
+        info!("Syncing checkpoints to latest");
+        self.sync_checkpoint_list_to_latest()
+            .await
+            .context("Failed to sync checkpoints")?;
+        info!("Synced checkpoints to latest");
+
+        // Get the local checkpoint list
+        let checkpoints_list: CheckpointsList = self.read_checkpoint_list()?;
+        debug!(checkpoints = ?checkpoints_list.checkpoints, "Loaded checkpoint list");
+
+        // Retrieve highest epoch committee id that was registered on dWallet newtwork
+        let mut latest_registered_epoch_committee_id = self.retrieve_highest_epoch().await?;
+        info!(
+            epoch = latest_registered_epoch_committee_id,
+            "Latest epoch committee registered on dWallet network"
+        );
+
+        // Resume after the last checkpoint processed by a previous run, confirming
+        // the committee chain still holds at that point
+        let mut prev_committee = self.load_genesis_committee()?;
+        let mut resume_index = 0;
+        if let Some(last_processed) = self.read_sync_state()?.last_processed_checkpoint {
+            if let Some(index) = checkpoints_list
+                .checkpoints
+                .iter()
+                .position(|ckp_id| *ckp_id == last_processed)
+            {
+                let summary = self.read_checkpoint(last_processed)?;
+                let committee = self.committee_for_checkpoint(last_processed)?;
+                prev_committee = self.verify_summary(&summary, &committee).context(
+                    "Committee chain broken at resume point, remove sync_state.yaml to resync",
+                )?;
+                resume_index = index + 1;
+                info!(checkpoint = last_processed, "Resuming after checkpoint");
+            }
+        }
+
+        // The committee object registered by the last submission of this run
+        let mut prev_committee_object_ref = None;
+        let mut protocol_versions = self.read_protocol_versions()?;
+
+        // Read the pending checkpoints, downloading those not stored, and make sure they
+        // close consecutive epochs before chaining committees through them. The committee
+        // chain cannot be followed past a failed download, so the checkpoints after it are
+        // left for the next sync.
+        let pending = &checkpoints_list.checkpoints[resume_index..];
+        let mut summaries = Vec::with_capacity(pending.len());
+        let mut failure: Option<(u64, anyhow::Error)> = None;
+        let mut downloaded = 0;
+        for ckp_id in pending {
+            if self.stop_requested() {
+                info!(
+                    checkpoint = ckp_id,
+                    "Sync stopped before downloading checkpoint"
+                );
+                return Ok(());
+            }
+            let summary = if self.has_checkpoint(*ckp_id) {
+                self.read_checkpoint(*ckp_id)?
+            } else {
+                // Download the checkpoint from the server
+                downloaded += 1;
+                match self
+                    .download_checkpoint_summary(*ckp_id)
+                    .await
+                    .context("Failed to download checkpoint")
+                {
+                    Ok(summary) => summary,
+                    Err(e) if options.continue_on_error => {
+                        warn!(
+                            checkpoint = ckp_id,
+                            error = ?e,
+                            "Download failed, processing the checkpoints before it"
+                        );
+                        failure = Some((*ckp_id, e));
+                        break;
+                    }
+                    Err(e) => return Err(e),
+                }
+            };
+            summaries.push(summary);
+        }
+        check_contiguous_epochs(&summaries, prev_committee.epoch)?;
+        let processable = summaries.len();
+
+        let progress = if options.show_progress && std::io::stdout().is_terminal() {
+            ProgressBar::with_draw_target(Some(pending.len() as u64), ProgressDrawTarget::stdout())
+        } else {
+            ProgressBar::hidden()
+        };
+        progress.set_style(ProgressStyle::with_template(
+            "{bar:40} {pos}/{len} epochs, {msg}, ETA {eta}",
+        )?);
+
+        // Check the signatures of all checkpoints
+        for summary in summaries {
+            let ckp_id = summary.sequence_number;
+            // Stop between checkpoints only, so a submission is never left without its
+            // checkpoint, committee and sync state written
+            if self.stop_requested() {
+                progress.abandon();
+                info!(checkpoint = ckp_id, "Sync stopped before checkpoint");
+                return Ok(());
+            }
+            debug!(checkpoint = ckp_id, "Processing checkpoint");
+
+            let next_committee = self.verify_summary(&summary, &prev_committee)?;
+            let next_protocol_version = next_protocol_version_from_summary(&summary)?;
+            match protocol_versions.get(&summary.epoch()) {
+                Some(&version) if next_protocol_version == version + 1 => info!(
+                    epoch = next_committee.epoch,
+                    from = version,
+                    to = next_protocol_version,
+                    "Protocol upgrade"
+                ),
+                // Skipped or rolled back versions may change how later checkpoints decode
+                Some(&version) if next_protocol_version != version => warn!(
+                    epoch = next_committee.epoch,
+                    from = version,
+                    to = next_protocol_version,
+                    "Protocol version jump"
+                ),
+                _ => {}
+            }
+            progress.set_message(format!(
+                "epoch {}, {} downloaded, {} verified",
+                summary.epoch(),
+                downloaded,
+                progress.position() + 1
+            ));
+            debug!(
+                epoch = summary.epoch(),
+                checkpoint = ckp_id,
+                "Verified checkpoint"
+            );
+
+            // Check if the checkpoint needs to be submitted to the dwallet network, again
+            // right before submitting as another syncer may have registered it meanwhile
+            if latest_registered_epoch_committee_id < summary.epoch() {
+                latest_registered_epoch_committee_id = self.retrieve_highest_epoch().await?;
+                if latest_registered_epoch_committee_id >= summary.epoch() {
+                    info!(
+                        epoch = summary.epoch(),
+                        "Epoch committee already registered by another syncer, skipping"
+                    );
+                    // The next submission looks up the committee object registered here
+                    prev_committee_object_ref = None;
+                }
+            }
+            if latest_registered_epoch_committee_id < summary.epoch() {
+                prev_committee_object_ref = self
+                    .submit_new_state_committee(
+                        &summary,
+                        prev_committee_object_ref,
+                        options.dry_run,
+                    )
+                    .await?;
+                if options.dry_run {
+                    // Later submissions build on the committee object this one creates
+                    progress.abandon();
+                    info!("Dry run complete, stopping before the next submission");
+                    return Ok(());
+                }
+            }
+
+            // Write the checkpoint summary and the committee it hands over to files
+            self.write_checkpoint(&summary)?;
+            self.write_committee(&next_committee)?;
+            protocol_versions.insert(next_committee.epoch, next_protocol_version);
+            self.write_protocol_versions(&protocol_versions)?;
+            self.write_sync_state(&SyncState {
+                last_processed_checkpoint: Some(ckp_id),
+                ..self.read_sync_state()?
+            })?;
+
+            info!(
+                epoch = summary.epoch(),
+                checkpoint = ckp_id,
+                digest = %summary.digest(),
+                "Processed end of epoch checkpoint"
+            );
+
+            // Extract the new committee information
+            prev_committee = next_committee;
+            progress.inc(1);
+        }
+        progress.finish();
+
+        if let Some((failed, e)) = failure {
+            let left = pending.len() - processable;
+            anyhow::bail!(
+                "{} checkpoint(s) could not be processed, the committee chain stops before \
+                 checkpoint {}:\n  {}: {:#}",
+                left,
+                failed,
+                failed,
+                e
+            );
+        }
+        Ok(())
+    }
+
+    /// Submits an end-of-epoch checkpoint to the dWallet network, registering the
+    /// committee it hands over to, and returns the created committee object.
+    ///
+    /// The committee object of the previous epoch is looked up on the dWallet network
+    /// unless `prev_committee_object_ref` is given. A dry run logs the transaction
+    /// instead of executing it and returns `None`.
+    #[tracing::instrument(skip(self, summary, prev_committee_object_ref), fields(epoch = summary.epoch(), checkpoint = summary.sequence_number))]
+    async fn submit_new_state_committee(
+        &self,
+        summary: &CertifiedCheckpointSummary,
+        prev_committee_object_ref: Option<ObjectRef>,
+        dry_run: bool,
+    ) -> anyhow::Result<Option<ObjectRef>> {
+        if let Some(stub) = &self.network_stub {
+            return stub
+                .submit_committee(summary, prev_committee_object_ref, dry_run)
+                .await;
+        }
+        let mut ptb = ProgrammableTransactionBuilder::new();
+
+        let prev_committee_object_ref_dwltn = match prev_committee_object_ref {
+            Some(object_ref) => object_ref,
+            None => {
+                let prev_epoch = summary.epoch().checked_sub(1).with_context(|| {
+                    format!(
+                        "Checkpoint {} of epoch 0 has no previous committee",
+                        summary.sequence_number
+                    )
+                })?;
+                let prev_committee_object_id = self
+                    .retrieve_epoch_committee_id_by_epoch(prev_epoch)
+                    .await?;
+                self.get_object_ref_by_id(prev_committee_object_id).await?
+            }
+        };
+
+        let registry_object_id = self.config.registry_object_id()?;
+        let registry_initial_shared_version = self
+            .registry_initial_shared_version(registry_object_id)
+            .await?;
+
+        let registry_arg = ptb
+            .obj(ObjectArg::SharedObject {
+                id: registry_object_id,
+                initial_shared_version: registry_initial_shared_version,
+                mutable: true,
+            })
+            .context("Passing the dWallet registry")?;
+        let prev_committee_arg = ptb
+            .obj(ObjectArg::ImmOrOwnedObject(prev_committee_object_ref_dwltn))
+            .context("Passing the previous epoch committee")?;
+        let new_checkpoint_summary_arg = ptb
+            .pure(bcs::to_bytes(summary).context("Serializing the checkpoint summary")?)
+            .context("Passing the checkpoint summary")?;
+
+        let call = ProgrammableMoveCall {
+            package: self.config.dwltn_state_proof_package_id,
+            module: Identifier::new("sui_state_proof").unwrap(),
+            function: Identifier::new("submit_new_state_committee").unwrap(),
+            type_arguments: vec![],
+            arguments: vec![registry_arg, prev_committee_arg, new_checkpoint_summary_arg],
+        };
+
+        ptb.command(Command::MoveCall(Box::new(call)));
+
+        let builder = ptb.finish();
+        let executed = match self.execute_dwallet_transaction(builder, dry_run).await {
+            Ok(None) => return Ok(None),
+            Ok(Some(response)) => Ok(response),
+            Err(e) => Err(e),
+        };
+
+        let failure = match &executed {
+            Ok(response) => match response.effects.as_ref().map(|effects| effects.status()) {
+                Some(SuiExecutionStatus::Failure { error }) => Some(error.clone()),
+                _ => None,
+            },
+            Err(e) => Some(format!("{:#}", e)),
+        };
+        if let Some(error) = failure {
+            // Another syncer may have registered this epoch after the check before submitting
+            if self.retrieve_highest_epoch().await? >= summary.epoch() {
+                warn!(%error, "Epoch committee already registered by another syncer, skipping");
+                return Ok(None);
+            }
+            return Err(anyhow!("Submitting the epoch committee failed: {}", error));
+        }
+        let transaction_response = executed?;
+        self.metrics.dwallet_submissions.inc();
+
+        let object_changes = transaction_response.object_changes.with_context(|| {
+            format!(
+                "No object changes in the response of transaction {}",
+                transaction_response.digest
+            )
+        })?;
+
+        // The created committee object is passed to the next submission directly,
+        // without waiting for it to be readable on the full node
+        let committee_object_ref = find_created_object(&object_changes, "EpochCommittee")
+            .with_context(|| format!("Transaction {}", transaction_response.digest))?;
+        info!(
+            digest = %transaction_response.digest,
+            committee = %committee_object_ref.0,
+            "Registered epoch committee"
+        );
+        for event in transaction_response
+            .events
+            .iter()
+            .flat_map(|events| events.data.iter())
+        {
+            debug!(type_ = %event.type_, contents = %event.parsed_json, "Submission event");
+        }
+
+        Ok(Some(committee_object_ref))
+    }
+
+    /// Verifies transactions `tids` and submits their state proofs to the dWallet network in
+    /// one transaction, with a `create_dwallet_wrapper` call per transaction sharing the gas.
+    ///
+    /// Each checkpoint including some of them is downloaded once. A transaction that cannot
+    /// be proven, e.g. because the committee of its epoch is not registered yet, is left out
+    /// and reported in [`ProofSubmission::failed`] instead of failing the batch.
+    pub async fn submit_transaction_proofs(
+        &self,
+        tids: &[TransactionDigest],
+        dry_run: bool,
+    ) -> anyhow::Result<ProofSubmission> {
+        let mut submission = ProofSubmission::default();
+
+        let mut tids_by_checkpoint: BTreeMap<u64, Vec<TransactionDigest>> = BTreeMap::new();
+        for tid in tids {
+            match self.transaction_checkpoint(*tid).await {
+                Ok(seq) => tids_by_checkpoint.entry(seq).or_default().push(*tid),
+                Err(e) => submission.failed.push((*tid, e)),
+            }
+        }
+
+        let mut proofs = vec![];
+        for (seq, tids) in tids_by_checkpoint {
+            let checkpoint = match self.get_full_checkpoint(seq).await {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => {
+                    let reason = format!("Failed to download full checkpoint {}: {:#}", seq, e);
+                    submission
+                        .failed
+                        .extend(tids.into_iter().map(|tid| (tid, anyhow!(reason.clone()))));
+                    continue;
+                }
+            };
+            let committee = match self.committee_for_full_checkpoint(seq, &checkpoint).await {
+                Ok(committee) => committee,
+                Err(e) => {
+                    let reason = format!("No committee for checkpoint {}: {:#}", seq, e);
+                    submission
+                        .failed
+                        .extend(tids.into_iter().map(|tid| (tid, anyhow!(reason.clone()))));
+                    continue;
+                }
+            };
+            for tid in tids {
+                match build_transaction_proof(&checkpoint, &committee, tid) {
+                    Ok(proof) => proofs.push(proof),
+                    Err(e) => submission.failed.push((tid, e.into())),
+                }
+            }
+        }
+        if proofs.is_empty() {
+            return Ok(submission);
+        }
+
+        // Each proof is checked against the committee of its epoch registered on the dWallet
+        // network, looked up once per epoch
+        let mut committee_ids: BTreeMap<u64, Result<ObjectID, String>> = BTreeMap::new();
+        let mut included = vec![];
+        for proof in proofs {
+            let epoch = proof.checkpoint_summary.epoch();
+            if !committee_ids.contains_key(&epoch) {
+                let committee_id = self
+                    .retrieve_epoch_committee_id_by_epoch(epoch)
+                    .await
+                    .map_err(|e| {
+                        format!(
+                            "Committee of epoch {} is not registered on the dWallet network: {:#}",
+                            epoch, e
+                        )
+                    });
+                committee_ids.insert(epoch, committee_id);
+            }
+            match &committee_ids[&epoch] {
+                Ok(committee_id) => included.push((proof, *committee_id)),
+                Err(reason) => submission
+                    .failed
+                    .push((*proof.transaction_digest(), anyhow!(reason.clone()))),
+            }
+        }
+        if included.is_empty() {
+            return Ok(submission);
+        }
+
+        let included_tids: Vec<_> = included
+            .iter()
+            .map(|(proof, _)| *proof.transaction_digest())
+            .collect();
+        match self.execute_proofs(&included, dry_run).await {
+            Ok(digest) => {
+                if let Some(digest) = digest {
+                    info!(%digest, proofs = included_tids.len(), "Submitted transaction proofs");
+                }
+                submission.digest = digest;
+                submission.submitted = included_tids;
+            }
+            Err(e) => {
+                let reason = format!("{:#}", e);
+                submission.failed.extend(
+                    included_tids
+                        .into_iter()
+                        .map(|tid| (tid, anyhow!(reason.clone()))),
+                );
+            }
+        }
+        Ok(submission)
+    }
+
+    /// Submits `proofs` in one dWallet transaction, each checked against the committee
+    /// object given with it, and returns its digest, or `None` in a dry run. A transaction
+    /// that executes but fails is an error.
+    async fn execute_proofs(
+        &self,
+        proofs: &[(TransactionProof, ObjectID)],
+        dry_run: bool,
+    ) -> anyhow::Result<Option<TransactionDigest>> {
+        if let Some(stub) = &self.network_stub {
+            return stub.submit_proofs(proofs, dry_run).await;
+        }
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let config_object_id = self.config.state_proof_config_object_id()?;
+        let config_arg = ptb.obj(self.object_arg(config_object_id, false).await?)?;
+
+        let mut committee_args: BTreeMap<ObjectID, Argument> = BTreeMap::new();
+        for (proof, committee_object_id) in proofs {
+            let committee_arg = match committee_args.get(committee_object_id) {
+                Some(arg) => *arg,
+                None => {
+                    let arg = ptb.obj(self.object_arg(*committee_object_id, false).await?)?;
+                    committee_args.insert(*committee_object_id, arg);
+                    arg
+                }
+            };
+
+            let summary_arg = ptb.pure(bcs::to_bytes(&proof.checkpoint_summary)?)?;
+            let contents_arg = ptb.pure(bcs::to_bytes(&proof.checkpoint_contents)?)?;
+            let transaction_arg = ptb.pure(bcs::to_bytes(&proof.transaction)?)?;
+            let call = ProgrammableMoveCall {
+                package: self.config.dwltn_state_proof_package_id,
+                module: Identifier::new("sui_state_proof").unwrap(),
+                function: Identifier::new("create_dwallet_wrapper").unwrap(),
+                type_arguments: vec![],
+                arguments: vec![
+                    config_arg,
+                    committee_arg,
+                    summary_arg,
+                    contents_arg,
+                    transaction_arg,
+                ],
+            };
+            ptb.command(Command::MoveCall(Box::new(call)));
+        }
+
+        let Some(response) = self
+            .execute_dwallet_transaction(ptb.finish(), dry_run)
+            .await
+            .context("Submitting the proofs failed")?
+        else {
+            return Ok(None);
+        };
+        if let Some(SuiExecutionStatus::Failure { error }) =
+            response.effects.as_ref().map(|effects| effects.status())
+        {
+            return Err(anyhow!(
+                "dWallet transaction {} failed: {}",
+                response.digest,
+                error
+            ));
+        }
+        Ok(Some(response.digest))
+    }
+
+    /// Pays for, signs and executes `builder` on the dWallet network, with gas reserved
+    /// from the gas station if one is configured. A dry run logs the transaction instead
+    /// of executing it and returns `None`, without reserving gas from the gas station.
+    ///
+    /// The response is returned whatever the execution status, for the caller to check.
+    pub async fn execute_dwallet_transaction(
+        &self,
+        builder: ProgrammableTransaction,
+        dry_run: bool,
+    ) -> anyhow::Result<Option<SuiTransactionBlockResponse>> {
+        let gas_price = self
+            .dwallet_request("Querying the dWallet gas price", |client| {
+                client.read_api().get_reference_gas_price()
+            })
+            .await?;
+        let dwallet_client = self.dwallet_client().await?;
+
+        let keystore = self.config.keystore()?;
+
+        let sender = select_sender(&keystore, self.config.dwltn_sender_address)?;
+        let gas_budget = self
+            .timed(
+                "Estimating the dWallet gas budget",
+                estimate_gas_budget(
+                    dwallet_client,
+                    sender,
+                    &builder,
+                    gas_price,
+                    self.config.gas_budget_margin_percent,
+                ),
+            )
+            .await
+            .unwrap_or(DEFAULT_GAS_BUDGET);
+
+        // The gas station has no way to release a reservation, so a dry run never makes one
+        if dry_run && self.gas_station.is_some() {
+            info!(
+                transaction = %Base64::encode(bcs::to_bytes(&builder)?),
+                gas_budget,
+                "Dry run, not reserving gas from the gas station nor executing the transaction"
+            );
+            return Ok(None);
+        }
+
+        // Pay for gas with coins reserved from the gas station if one is configured,
+        // otherwise with the sender's own coins
+        let (tx_data, reservation_id) = match &self.gas_station {
+            Some(gas_station) => {
+                let reservation = gas_station
+                    .reserve_gas(gas_budget, GAS_RESERVATION_SECS)
+                    .await?;
+                let tx_data = TransactionData::new_programmable_allow_sponsor(
+                    sender,
+                    reservation.gas_coins,
+                    builder,
+                    gas_budget,
+                    gas_price,
+                    reservation.sponsor,
+                );
+                (tx_data, Some(reservation.reservation_id))
+            }
+            None => {
+                let coin_gas = self
+                    .timed(
+                        "Selecting dWallet gas coin",
+                        select_gas_coin(dwallet_client, sender, gas_budget),
+                    )
+                    .await??;
+                let tx_data = TransactionData::new_programmable(
+                    sender,
+                    vec![coin_gas.object_ref()],
+                    builder,
+                    gas_budget,
+                    gas_price,
+                );
+                (tx_data, None)
+            }
+        };
+
+        if dry_run {
+            info!(
+                tx_bytes = %Base64::encode(bcs::to_bytes(&tx_data)?),
+                gas_coins = ?tx_data.gas(),
+                "Dry run, not executing the transaction"
+            );
+            return Ok(None);
+        }
+
+        // 4) sign transaction
+        let signature = keystore
+            .sign_secure(&sender, &tx_data, Intent::sui_transaction())
+            .with_context(|| format!("Signing the dWallet transaction as {}", sender))?;
+
+        // 5) execute the transaction
+        info!("Executing the transaction...");
+        let response = match (&self.gas_station, reservation_id) {
+            (Some(gas_station), Some(reservation_id)) => {
+                let effects = gas_station
+                    .execute_tx(reservation_id, &tx_data, &signature)
+                    .await?;
+                // The gas station only returns the effects, so read back the object changes
+                self.wait_for_dwallet_transaction(*effects.transaction_digest())
+                    .await?
+            }
+            _ => {
+                // Resending the same signed transaction on a retry executes it at most once
+                let transaction = Transaction::from_data(tx_data, vec![signature]);
+                self.dwallet_request("Executing the dWallet transaction", |client| {
+                    client.quorum_driver_api().execute_transaction_block(
+                        transaction.clone(),
+                        SuiTransactionBlockResponseOptions::full_content(),
+                        Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+                    )
+                })
+                .await?
+            }
+        };
+        Ok(Some(response))
+    }
+
+    /// The initial shared version of the dWallet registry, which never changes, so it is
+    /// fetched once per handle and checked against the one in the config if set.
+    async fn registry_initial_shared_version(
+        &self,
+        registry_object_id: ObjectID,
+    ) -> anyhow::Result<SequenceNumber> {
+        self.registry_initial_shared_version
+            .get_or_try_init(|| async {
+                let res = self
+                    .dwallet_request("Fetching the dWallet registry", |client| {
+                        client.read_api().get_object_with_options(
+                            registry_object_id,
+                            SuiObjectDataOptions::new().with_owner(),
+                        )
+                    })
+                    .await?;
+                let registry_owner = res
+                    .owner()
+                    .ok_or_else(|| anyhow!("dWallet registry {} not found", registry_object_id))?;
+                let Owner::Shared {
+                    initial_shared_version,
+                } = registry_owner
+                else {
+                    return Err(anyhow!("Expected a Shared owner"));
+                };
+                if let Some(configured) = self.config.dwltn_registry_initial_shared_version {
+                    anyhow::ensure!(
+                        configured == initial_shared_version.value(),
+                        "dwltn_registry_initial_shared_version {} in the config does not match \
+                         the initial shared version {} of registry {}",
+                        configured,
+                        initial_shared_version,
+                        registry_object_id
+                    );
+                }
+                Ok(initial_shared_version)
+            })
+            .await
+            .copied()
+    }
+
+    /// Polls the dWallet full node until transaction `digest` is readable, up to the
+    /// request timeout.
+    async fn wait_for_dwallet_transaction(
+        &self,
+        digest: TransactionDigest,
+    ) -> anyhow::Result<SuiTransactionBlockResponse> {
+        let backoff = ExponentialBackoff {
+            max_elapsed_time: Some(self.config.request_timeout()),
+            ..Default::default()
+        };
+        backoff::future::retry(backoff, || async {
+            let client = self
+                .dwallet_client()
+                .await
+                .map_err(backoff::Error::permanent)?;
+            client
+                .read_api()
+                .get_transaction_with_options(
+                    digest,
+                    SuiTransactionBlockResponseOptions::full_content(),
+                )
+                .await
+                .map_err(|e| {
+                    backoff::Error::transient(anyhow!(
+                        "Transaction {} not readable on the dWallet full node: {}",
+                        digest,
+                        e
+                    ))
+                })
+        })
+        .await
+    }
+
+    /// [`verify_end_of_epoch_summary`], counting the outcome in the metrics
+    fn verify_summary(
+        &self,
+        summary: &CertifiedCheckpointSummary,
+        committee: &Committee,
+    ) -> anyhow::Result<Committee> {
+        let result = verify_end_of_epoch_summary(summary, committee);
+        match result {
+            Ok(_) => self.metrics.checkpoints_verified.inc(),
+            Err(_) => self.metrics.verification_failures.inc(),
+        }
+        Ok(result?)
+    }
+
+    /// [`extract_verified_effects_and_events`], timed and counting failures in the metrics
+    fn extract_effects_and_events(
+        &self,
+        full_checkpoint: &CheckpointData,
+        committee: &Committee,
+        tid: TransactionDigest,
+    ) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
+        let _timer = self
+            .metrics
+            .start_timer("extract_verified_effects_and_events");
+        let result = extract_verified_effects_and_events(full_checkpoint, committee, tid);
+        if result.is_err() {
+            self.metrics.verification_failures.inc();
+        }
+        Ok(result?)
+    }
+
+    /// Derives the committee that signs checkpoint `seq` from the stored end-of-epoch
+    /// checkpoint preceding it, or from genesis if there is none.
+    pub fn committee_for_checkpoint(&self, seq: u64) -> anyhow::Result<Committee> {
+        // Find the stored end-of-epoch checkpoint before the seq checkpoint
+        let checkpoints_list: CheckpointsList = self.read_checkpoint_list()?;
+        let prev_ckp_id = checkpoints_list
+            .checkpoints
+            .iter()
+            .filter(|ckp_id| **ckp_id < seq)
+            .last();
+
+        let Some(prev_ckp_id) = prev_ckp_id else {
+            // Since we did not find an end-of-epoch checkpoint before it we use the genesis
+            return self.load_genesis_committee();
+        };
+
+        // Get the committee from the previous checkpoint
+        let prev_ckp = self.read_checkpoint(*prev_ckp_id).with_context(|| {
+            format!(
+                "Stored checkpoint {} is missing. Need to Sync.",
+                prev_ckp_id
+            )
+        })?;
+        Ok(next_committee_from_summary(&prev_ckp)?)
+    }
+
+    /// Derives the committee of `epoch` by verifying the stored end-of-epoch checkpoints
+    /// from genesis up to the one that hands over to it.
+    pub fn committee_for_epoch(&self, epoch: u64) -> anyhow::Result<Committee> {
+        let mut committee = self.load_genesis_committee()?;
+        for ckp_id in self.read_checkpoint_list()?.checkpoints {
+            if committee.epoch >= epoch {
+                break;
+            }
+            let summary = self.read_checkpoint(ckp_id).with_context(|| {
+                format!("Stored checkpoint {} is missing. Need to Sync.", ckp_id)
+            })?;
+            committee = self.verify_summary(&summary, &committee)?;
+        }
+        if committee.epoch != epoch {
+            return Err(LightClientError::NeedsSync {
+                have_epoch: committee.epoch,
+                want_epoch: epoch,
+            }
+            .into());
+        }
+        Ok(committee)
+    }
+
+    /// Fetches the committee of `epoch` as the full node reports it, i.e. the validators and
+    /// stakes of the Sui system state object (0x5) in that epoch.
+    ///
+    /// Nothing here is verified: compare it with [`LightClient::committee_for_epoch`], e.g.
+    /// with [`crate::diff_committees`], to cross-check the committee chain derived from the
+    /// end-of-epoch checkpoints against what the full node claims.
+    pub async fn fetch_onchain_committee(&self, epoch: u64) -> anyhow::Result<Committee> {
+        let governance_api = self.sui_client().await?.governance_api();
+        let committee_info = self
+            .timed(
+                "Fetching the on-chain committee",
+                governance_api.get_committee_info(Some(BigInt::from(epoch))),
+            )
+            .await?
+            .with_context(|| format!("Unable to fetch the committee of epoch {}", epoch))?;
+        if committee_info.epoch != epoch {
+            return Err(anyhow!(
+                "Full node returned the committee of epoch {} instead of epoch {}",
+                committee_info.epoch,
+                epoch
+            ));
+        }
+        Ok(committee_from_stakes(epoch, committee_info.validators)?)
+    }
+
+    /// Re-verifies a stored checkpoint summary against the committee of its epoch
+    /// without touching the network.
+    pub fn verify_stored_checkpoint(&self, seq: u64) -> anyhow::Result<CertifiedCheckpointSummary> {
+        let summary = self
+            .read_checkpoint(seq)
+            .with_context(|| format!("Unable to read stored checkpoint {}", seq))?;
+        let committee = self.committee_for_checkpoint(seq)?;
+        summary
+            .clone()
+            .try_into_verified(&committee)
+            .with_context(|| {
+                format!(
+                    "Checkpoint {} does not verify against the committee of epoch {}",
+                    seq, committee.epoch
+                )
+            })?;
+        Ok(summary)
+    }
+
+    /// Verifies checkpoint `seq`, stored or else downloaded, against `committee` instead of
+    /// the committee derived from the stored checkpoints.
+    pub async fn verify_checkpoint_against(
+        &self,
+        seq: u64,
+        committee: &Committee,
+    ) -> anyhow::Result<CertifiedCheckpointSummary> {
+        let summary = if self.has_checkpoint(seq) {
+            self.read_checkpoint(seq)?
+        } else {
+            self.download_checkpoint_summary(seq).await?
+        };
+        ensure_committee_epoch(committee, &summary)?;
+        verify_checkpoint_summary(&summary, committee)?;
+        Ok(summary)
+    }
+
+    /// Downloads checkpoint `seq`, end-of-epoch or not, verifies it against the committee
+    /// of its epoch and stores it, without adding it to the checkpoint list.
+    pub async fn cache_checkpoint(&self, seq: u64) -> anyhow::Result<CertifiedCheckpointSummary> {
+        if self.checkpoint_store.contains(seq) {
+            return self.read_checkpoint(seq);
+        }
+
+        self.check_genesis().await?;
+        let summary = self.download_checkpoint_summary(seq).await?;
+        let committee = self.committee_for_checkpoint(seq)?;
+        if committee.epoch != summary.epoch() {
+            return Err(LightClientError::NeedsSync {
+                have_epoch: committee.epoch,
+                want_epoch: summary.epoch(),
+            }
+            .into());
+        }
+        verify_checkpoint_summary(&summary, &committee)?;
+        self.write_checkpoint(&summary)?;
+        Ok(summary)
+    }
+
+    /// Looks up the sequence number of the checkpoint that includes `tid`.
+    async fn transaction_checkpoint(&self, tid: TransactionDigest) -> anyhow::Result<u64> {
+        if let Some(stub) = &self.network_stub {
+            return stub.transaction_checkpoint(tid).await;
+        }
+        let options = SuiTransactionBlockResponseOptions::new();
+        let read_api = self.sui_client().await?.read_api();
+        self.timed(
+            &format!("Fetching transaction {}", tid),
+            read_api.get_transaction_with_options(tid, options),
+        )
+        .await?
+        .map_err(|e| {
+            if e.to_string().contains(TRANSACTION_NOT_FOUND_MESSAGE) {
+                LightClientError::TransactionNotFound(tid)
+            } else {
+                LightClientError::Rpc(format!("fetching transaction {}: {}", tid, e))
+            }
+        })?
+        .checkpoint
+        // An executed transaction has no checkpoint until one includes it
+        .ok_or(LightClientError::TransactionPending(tid).into())
+    }
+
+    /// Looks up the sequence number of the checkpoint that includes `tid`, polling while
+    /// the transaction is pending for at most `timeout`.
+    pub async fn wait_for_transaction_checkpoint(
+        &self,
+        tid: TransactionDigest,
+        timeout: Duration,
+    ) -> anyhow::Result<u64> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            match self.transaction_checkpoint(tid).await {
+                Err(e)
+                    if matches!(
+                        e.downcast_ref(),
+                        Some(LightClientError::TransactionPending(_))
+                    ) && tokio::time::Instant::now() + TRANSACTION_POLL_INTERVAL <= deadline =>
+                {
+                    debug!(%tid, "Transaction not in a checkpoint yet, polling");
+                    tokio::time::sleep(TRANSACTION_POLL_INTERVAL).await;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Gathers the stored end-of-epoch checkpoints and the full checkpoint including
+    /// transaction `tid` into a bundle, checked against the genesis committee before it is
+    /// returned.
+    pub async fn export_bundle(&self, tid: TransactionDigest) -> anyhow::Result<ProofBundle> {
+        self.check_genesis().await?;
+        let seq = self.transaction_checkpoint(tid).await?;
+        let full_checkpoint = self
+            .get_full_checkpoint(seq)
+            .await
+            .context("Failed to download full checkpoint")?;
+
+        let epoch = full_checkpoint.checkpoint_summary.epoch();
+        let mut end_of_epoch_checkpoints = vec![];
+        for ckp_id in self.read_checkpoint_list()?.checkpoints {
+            let summary = self.read_checkpoint(ckp_id).with_context(|| {
+                format!("Stored checkpoint {} is missing. Need to Sync.", ckp_id)
+            })?;
+            if summary.epoch() >= epoch {
+                break;
+            }
+            end_of_epoch_checkpoints.push(summary);
+        }
+
+        let bundle = ProofBundle {
+            transaction: tid,
+            end_of_epoch_checkpoints,
+            full_checkpoint,
+        };
+        bundle
+            .verify(&self.load_genesis_committee()?)
+            .context("Exported bundle does not verify. Need to Sync.")?;
+        Ok(bundle)
+    }
+
+    /// Returns the committee that signs the downloaded checkpoint `seq`, after checking the
+    /// genesis file the committees derive from against the targeted chain.
+    ///
+    /// The committee is derived from the stored end-of-epoch checkpoints rather than read
+    /// from the committee files, which are not trusted on their own. If the stored
+    /// checkpoints stop short of its epoch, up to [`MAX_AUTO_FETCH_EPOCHS`] missing ones
+    /// are downloaded and verified from the latest stored committee.
+    async fn committee_for_full_checkpoint(
+        &self,
+        seq: u64,
+        full_checkpoint: &CheckpointData,
+    ) -> anyhow::Result<Committee> {
+        self.check_genesis().await?;
+        let epoch = full_checkpoint.checkpoint_summary.epoch();
+
+        let mut committee = self.committee_for_checkpoint(seq)?;
+        if committee.epoch == epoch {
+            // Repair a committee file that no longer matches the verified chain
+            if let Ok(cached) = self.read_committee(epoch) {
+                if cached != committee {
+                    warn!(
+                        epoch,
+                        "Cached committee does not match the stored checkpoints, rewriting it"
+                    );
+                    self.write_committee(&committee)?;
+                }
+            }
+            return Ok(committee);
+        }
+        anyhow::ensure!(
+            committee.epoch <= epoch,
+            "Checkpoint sequence number does not match. Need to Sync."
+        );
+        if epoch - committee.epoch > MAX_AUTO_FETCH_EPOCHS {
+            return Err(LightClientError::NeedsSync {
+                have_epoch: committee.epoch,
+                want_epoch: epoch,
+            })
+            .with_context(|| {
+                format!(
+                    "Checkpoint {} is more than {} epochs past the stored checkpoints",
+                    seq, MAX_AUTO_FETCH_EPOCHS
+                )
+            });
+        }
+
+        // Fill the gap between the stored checkpoints and the epoch of the checkpoint
+        while committee.epoch < epoch {
+            let summary = self
+                .download_end_of_epoch_summary(committee.epoch)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Unable to fetch the missing end of epoch checkpoint of epoch {}",
+                        committee.epoch
+                    )
+                })?;
+            committee = self.verify_summary(&summary, &committee)?;
+            self.write_committee(&committee)?;
+            info!(
+                epoch = summary.epoch(),
+                checkpoint = summary.sequence_number,
+                "Fetched missing end of epoch checkpoint"
+            );
+        }
+        Ok(committee)
+    }
+
+    pub async fn get_verified_effects_and_events(
+        &self,
+        tid: TransactionDigest,
+    ) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
+        // Lookup the transaction id and get the checkpoint sequence number
+        let seq = self.transaction_checkpoint(tid).await?;
+        self.get_verified_effects_and_events_at(tid, seq).await
+    }
+
+    /// Verifies the effects and events of transaction `tid` included in checkpoint `seq`,
+    /// without looking up its checkpoint on the full node.
+    pub async fn get_verified_effects_and_events_at(
+        &self,
+        tid: TransactionDigest,
+        seq: u64,
+    ) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
+        let (effects, events, _) = self
+            .get_verified_effects_and_events_with_committee(tid, Some(seq))
+            .await?;
+        Ok((effects, events))
+    }
+
+    /// Verifies the effects and events of transaction `tid`, in checkpoint `seq` if known,
+    /// and also returns the committee that signed its checkpoint.
+    pub async fn get_verified_effects_and_events_with_committee(
+        &self,
+        tid: TransactionDigest,
+        seq: Option<u64>,
+    ) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>, Committee)> {
+        let seq = match seq {
+            Some(seq) => seq,
+            None => self.transaction_checkpoint(tid).await?,
+        };
+
+        // Download the full checkpoint for this sequence number
+        let full_checkpoint = self
+            .get_full_checkpoint(seq)
+            .await
+            .context("Failed to download full checkpoint")?;
+        let committee = self
+            .committee_for_full_checkpoint(seq, &full_checkpoint)
+            .await?;
+
+        debug!(%tid, "Extracting effects and events");
+        let (effects, events) = self
+            .extract_effects_and_events(&full_checkpoint, &committee, tid)
+            .context("Cannot extract effects and events")?;
+        Ok((effects, events, committee))
+    }
+
+    /// Verifies transaction `tid`, in checkpoint `seq` if known, against `committee` if
+    /// given or else the committee that signed its checkpoint, and returns its data along
+    /// with its effects and events, all read from the same verified full checkpoint.
+    pub async fn get_verified_transaction(
+        &self,
+        tid: TransactionDigest,
+        seq: Option<u64>,
+        committee: Option<&Committee>,
+    ) -> anyhow::Result<VerifiedTransactionBlock> {
+        let seq = match seq {
+            Some(seq) => seq,
+            None => self.transaction_checkpoint(tid).await?,
+        };
+        let full_checkpoint = self
+            .get_full_checkpoint(seq)
+            .await
+            .context("Failed to download full checkpoint")?;
+        let committee = match committee {
+            Some(committee) => {
+                ensure_committee_epoch(committee, &full_checkpoint.checkpoint_summary)?;
+                committee.clone()
+            }
+            None => {
+                self.committee_for_full_checkpoint(seq, &full_checkpoint)
+                    .await?
+            }
+        };
+
+        debug!(%tid, "Extracting transaction data, effects and events");
+        let (effects, events) = self
+            .extract_effects_and_events(&full_checkpoint, &committee, tid)
+            .context("Cannot extract effects and events")?;
+        let data = extract_verified_transaction_data(&full_checkpoint, &committee, tid)
+            .context("Cannot extract transaction data")?;
+        Ok(VerifiedTransactionBlock {
+            data,
+            effects,
+            events,
+            committee,
+        })
+    }
+
+    /// Verifies transaction `tid` and returns its sender and gas payment, read from the
+    /// verified full checkpoint rather than the full node.
+    pub async fn get_verified_sender_and_gas(
+        &self,
+        tid: TransactionDigest,
+    ) -> anyhow::Result<VerifiedSenderAndGas> {
+        let seq = self.transaction_checkpoint(tid).await?;
+        let full_checkpoint = self
+            .get_full_checkpoint(seq)
+            .await
+            .context("Failed to download full checkpoint")?;
+        let committee = self
+            .committee_for_full_checkpoint(seq, &full_checkpoint)
+            .await?;
+
+        let result = extract_verified_sender_and_gas(&full_checkpoint, &committee, tid);
+        if result.is_err() {
+            self.metrics.verification_failures.inc();
+        }
+        Ok(result.context("Cannot extract sender and gas")?)
+    }
+
+    /// Verifies the effects and events of transaction `tid`, in checkpoint `seq` if known,
+    /// against `committee` instead of the committee derived from the stored checkpoints.
+    pub async fn get_verified_effects_and_events_against(
+        &self,
+        tid: TransactionDigest,
+        seq: Option<u64>,
+        committee: &Committee,
+    ) -> anyhow::Result<(TransactionEffects, Option<TransactionEvents>)> {
+        let seq = match seq {
+            Some(seq) => seq,
+            None => self.transaction_checkpoint(tid).await?,
+        };
+        let full_checkpoint = self
+            .get_full_checkpoint(seq)
+            .await
+            .context("Failed to download full checkpoint")?;
+        ensure_committee_epoch(committee, &full_checkpoint.checkpoint_summary)?;
+
+        debug!(%tid, "Extracting effects and events");
+        self.extract_effects_and_events(&full_checkpoint, committee, tid)
+            .context("Cannot extract effects and events")
+    }
+
+    /// Verifies the effects and events of several transactions, downloading each
+    /// checkpoint they are included in only once.
+    pub async fn get_verified_effects_and_events_batch(
+        &self,
+        tids: &[TransactionDigest],
+    ) -> anyhow::Result<HashMap<TransactionDigest, (TransactionEffects, Option<TransactionEvents>)>>
+    {
+        // Group the transactions by the checkpoint that includes them
+        let mut tids_by_checkpoint: BTreeMap<u64, Vec<TransactionDigest>> = BTreeMap::new();
+        for tid in tids {
+            let seq = self.transaction_checkpoint(*tid).await?;
+            tids_by_checkpoint.entry(seq).or_default().push(*tid);
+        }
+
+        // Prefetch the full checkpoints concurrently, holding at most a window of them in
+        // memory, and verify each window sequentially
+        let seqs: Vec<u64> = tids_by_checkpoint.keys().copied().collect();
+        let mut verified = HashMap::new();
+        for window in seqs.chunks(self.config.prefetch_max_checkpoints.max(1)) {
+            let mut prefetched = stream::iter(window.iter().copied())
+                .map(|seq| async move { (seq, self.get_full_checkpoint(seq).await) })
+                .buffer_unordered(self.config.prefetch_concurrency.max(1))
+                .collect::<BTreeMap<_, _>>()
+                .await;
+
+            for seq in window {
+                let full_checkpoint = prefetched
+                    .remove(seq)
+                    .expect("every checkpoint of the window is prefetched")
+                    .with_context(|| format!("Failed to download full checkpoint {}", seq))?;
+                let committee = self
+                    .committee_for_full_checkpoint(*seq, &full_checkpoint)
+                    .await?;
+
+                for tid in &tids_by_checkpoint[seq] {
+                    debug!(%tid, checkpoint = seq, "Extracting effects and events");
+                    let effects_and_events = self
+                        .extract_effects_and_events(&full_checkpoint, &committee, *tid)
+                        .with_context(|| format!("Cannot extract effects and events of {}", tid))?;
+                    verified.insert(*tid, effects_and_events);
+                }
+            }
+        }
+
+        Ok(verified)
+    }
+
+    /// Downloads full checkpoint `seq` once and returns the verified effects and events of
+    /// every transaction it contains, in order.
+    pub async fn verify_checkpoint(&self, seq: u64) -> anyhow::Result<Vec<VerifiedTransaction>> {
+        let full_checkpoint = self
+            .get_full_checkpoint(seq)
+            .await
+            .context("Failed to download full checkpoint")?;
+        let committee = self
+            .committee_for_full_checkpoint(seq, &full_checkpoint)
+            .await?;
+
+        let _timer = self.metrics.start_timer("verify_checkpoint_transactions");
+        let result = verify_checkpoint_transactions(&full_checkpoint, &committee);
+        if result.is_err() {
+            self.metrics.verification_failures.inc();
+        }
+        Ok(result.with_context(|| format!("Cannot verify checkpoint {}", seq))?)
+    }
+
+    /// Fetches an object and verifies it against the effects of the transaction that
+    /// wrote it, themselves verified in their checkpoint.
+    pub async fn get_verified_object(&self, id: ObjectID) -> anyhow::Result<Object> {
+        debug!(%id, "Getting object");
+        let object = self.fetch_object(id).await?;
+        self.authenticate_object(&object).await?;
+        Ok(object)
+    }
+
+    /// Fetches an object without authenticating it, for resolving the layouts of types
+    /// only, e.g. packages fetched by a
+    /// [`RemotePackageStore::for_resolution`](crate::RemotePackageStore::for_resolution).
+    ///
+    /// A wrong package makes verified values decode wrongly, but cannot make unverified
+    /// effects, events or objects pass verification.
+    pub async fn get_object_for_resolution(&self, id: ObjectID) -> anyhow::Result<Object> {
+        debug!(%id, "Getting object for type resolution");
+        self.fetch_object(id).await
+    }
+
+    /// Fetches the latest version of an object from the full node, unauthenticated.
+    async fn fetch_object(&self, id: ObjectID) -> anyhow::Result<Object> {
+        let read_api = self
+            .sui_client()
+            .await
+            .context("building sui client for object fetch")?
+            .read_api();
+        let object_json = self
+            .timed(
+                &format!("Fetching object {}", id),
+                read_api.get_object_with_options(id, SuiObjectDataOptions::bcs_lossless()),
+            )
+            .await?
+            .with_context(|| format!("fetching object {} from full node", id))?;
+        let object = object_json
+            .into_object()
+            .map_err(|e| anyhow!("object {} not found on full node: {}", id, e))?;
+        let object: Object = object
+            .try_into()
+            .with_context(|| format!("reconstructing object {} from full node data", id))?;
+        Ok(object)
+    }
+
+    /// Fetches an object as it was at `version` and verifies it against the effects of
+    /// the transaction that wrote that version, e.g. for historical audits.
+    pub async fn get_verified_object_at_version(
+        &self,
+        id: ObjectID,
+        version: SequenceNumber,
+    ) -> anyhow::Result<Object> {
+        debug!(%id, %version, "Getting past object");
+
+        let read_api = self
+            .sui_client()
+            .await
+            .context("building sui client for object fetch")?
+            .read_api();
+        let response = self
+            .timed(
+                &format!("Fetching object {} at version {}", id, version),
+                read_api.try_get_parsed_past_object(
+                    id,
+                    version,
+                    SuiObjectDataOptions::bcs_lossless(),
+                ),
+            )
+            .await?
+            .with_context(|| format!("fetching object {} from full node", id))?;
+        let object = match response {
+            SuiPastObjectResponse::VersionFound(object) => object,
+            SuiPastObjectResponse::VersionNotFound(_, _) => {
+                return Err(anyhow!(
+                    "version {} of object {} not found on full node, it may have been pruned",
+                    version,
+                    id
+                ))
+            }
+            SuiPastObjectResponse::VersionTooHigh { latest_version, .. } => {
+                return Err(anyhow!(
+                    "version {} of object {} is past its latest version {}",
+                    version,
+                    id,
+                    latest_version
+                ))
+            }
+            SuiPastObjectResponse::ObjectDeleted(_) => {
+                return Err(anyhow!("object {} is deleted", id))
+            }
+            SuiPastObjectResponse::ObjectNotExists(_) => {
+                return Err(anyhow!("object {} not found on full node", id))
+            }
+        };
+        let object: Object = object
+            .try_into()
+            .with_context(|| format!("reconstructing object {} from full node data", id))?;
+        anyhow::ensure!(
+            object.version() == version,
+            "full node returned version {} of object {} instead of {}",
+            object.version(),
+            id,
+            version
+        );
+
+        self.authenticate_object(&object).await?;
+        Ok(object)
+    }
+
+    /// Checks an object against the verified effects of the transaction that wrote it.
+    ///
+    /// Verifying the effects never resolves Move types, so authenticating a package cannot
+    /// fetch packages in turn and recurse through a package store.
+    async fn authenticate_object(&self, object: &Object) -> anyhow::Result<()> {
+        let (effects, _) = self
+            .get_verified_effects_and_events(object.previous_transaction)
+            .await
+            .with_context(|| {
+                format!(
+                    "verifying effects of transaction {}",
+                    object.previous_transaction
+                )
+            })?;
+
+        // Check that this object ID, version and digest is in the effects
+        let target_object_ref = object.compute_object_reference();
+        effects
+            .all_changed_objects()
+            .iter()
+            .find(|object_ref| object_ref.0 == target_object_ref)
+            .ok_or(anyhow!(
+                "object {} not authenticated by its creating transaction {}",
+                target_object_ref.0,
+                object.previous_transaction
+            ))?;
+        Ok(())
+    }
+
+    /// Fetches and verifies the dynamic field of `parent` named by the BCS bytes
+    /// `name_bcs` of type `name_type`, e.g. a table entry.
+    ///
+    /// The field object id is derived from the parent and the name rather than looked up
+    /// on the full node, and the verified field must be owned by the parent.
+    pub async fn get_verified_dynamic_field(
+        &self,
+        parent: ObjectID,
+        name_type: &TypeTag,
+        name_bcs: &[u8],
+    ) -> anyhow::Result<Object> {
+        let id = derive_dynamic_field_id(parent, name_type, name_bcs)
+            .context("deriving dynamic field id")?;
+        debug!(%parent, %id, "Getting dynamic field");
+
+        let object = self.get_verified_object(id).await?;
+        anyhow::ensure!(
+            object.owner == Owner::ObjectOwner(parent.into()),
+            "dynamic field {} is not owned by {} but {}",
+            id,
+            parent,
+            object.owner
+        );
+        Ok(object)
+    }
+
+    /// Queries a page of events emitted by the dWallet state proof module, retrying on
+    /// failures.
+    async fn query_state_proof_events(&self, cursor: Option<EventID>) -> anyhow::Result<EventPage> {
+        let query = EventFilter::MoveModule {
+            package: self.config.dwltn_state_proof_package_id,
+            module: Identifier::from_str(&"sui_state_proof").unwrap(),
+        };
+
+        self.dwallet_request("Querying dWallet state proof events", |client| {
+            client
+                .event_api()
+                .query_events(query.clone(), cursor, Option::None, true)
+        })
+        .await
+    }
+
+    /// Returns the highest epoch whose committee was registered in the dWallet registry,
+    /// or 0 if none was submitted since it was initialized.
+    async fn retrieve_highest_epoch(&self) -> anyhow::Result<u64> {
+        if let Some(stub) = &self.network_stub {
+            return stub.highest_registered_epoch().await;
+        }
+        let registry_id = self.config.registry_object_id()?;
+        let mut max = 0;
+        let mut has_next = true;
+        let mut cursor = Option::None;
+        while has_next {
+            let res = self.query_state_proof_events(cursor).await?;
+            let page_max = res
+                .data
+                .iter()
+                .filter(|event| is_registry_event(&event.parsed_json, registry_id))
+                .filter_map(|event| event.parsed_json.get("epoch")?.as_str())
+                .map(u64::from_str)
+                .collect::<Result<Vec<_>, _>>()
+                .context("Invalid epoch in state proof event")?
+                .into_iter()
+                .max();
+            max = max.max(page_max.unwrap_or(0));
+
+            cursor = res.next_cursor;
+            has_next = res.has_next_page;
+        }
+        Ok(max)
+    }
+
+    /// Returns the id of the committee object of `target_epoch` registered in the dWallet
+    /// registry, failing with [`LightClientError::EpochCommitteeNotRegistered`] if none is.
+    async fn retrieve_epoch_committee_id_by_epoch(
+        &self,
+        target_epoch: u64,
+    ) -> anyhow::Result<ObjectID> {
+        if let Some(stub) = &self.network_stub {
+            return stub.epoch_committee_id(target_epoch).await;
+        }
+        let registry_id = self.config.registry_object_id()?;
+        let mut has_next = true;
+        let mut cursor = Option::None;
+        while has_next {
+            let res = self.query_state_proof_events(cursor).await?;
+            for event in &res.data {
+                let json = &event.parsed_json;
+                if !is_registry_event(json, registry_id) {
+                    continue;
+                }
+                let Some(epoch) = json.get("epoch").and_then(|epoch| epoch.as_str()) else {
+                    continue;
+                };
+                let epoch = u64::from_str(epoch).with_context(|| {
+                    format!("Invalid epoch in state proof event {:?}", event.id)
+                })?;
+                if epoch != target_epoch {
+                    continue;
+                }
+                let committee_id = json
+                    .get("epoch_committee_id")
+                    .and_then(|id| id.as_str())
+                    .with_context(|| {
+                        format!("No epoch_committee_id in state proof event {:?}", event.id)
+                    })?;
+                return ObjectID::from_hex_literal(committee_id).with_context(|| {
+                    format!(
+                        "Invalid epoch_committee_id in state proof event {:?}",
+                        event.id
+                    )
+                });
+            }
+
+            cursor = res.next_cursor;
+            has_next = res.has_next_page;
+        }
+
+        Err(LightClientError::EpochCommitteeNotRegistered {
+            epoch: target_epoch,
+        }
+        .into())
+    }
+
+    /// Builds the argument passing dWallet object `object_id` to a call, by reference to its
+    /// initial shared version if it is shared.
+    async fn object_arg(&self, object_id: ObjectID, mutable: bool) -> anyhow::Result<ObjectArg> {
+        let res = self
+            .dwallet_request(
+                &format!("Fetching dWallet object {}", object_id),
+                |client| {
+                    client.read_api().get_object_with_options(
+                        object_id,
+                        SuiObjectDataOptions::new().with_owner(),
+                    )
+                },
+            )
+            .await?;
+        let data = res
+            .data
+            .ok_or_else(|| anyhow!("dWallet object {} not found", object_id))?;
+        Ok(match data.owner {
+            Some(Owner::Shared {
+                initial_shared_version,
+            }) => ObjectArg::SharedObject {
+                id: object_id,
+                initial_shared_version,
+                mutable,
+            },
+            _ => ObjectArg::ImmOrOwnedObject(data.object_ref()),
+        })
+    }
+
+    async fn get_object_ref_by_id(&self, object_id: ObjectID) -> anyhow::Result<ObjectRef> {
+        let res = self
+            .dwallet_request(
+                &format!("Fetching dWallet object {}", object_id),
+                |client| {
+                    client.read_api().get_object_with_options(
+                        object_id,
+                        SuiObjectDataOptions::full_content().with_bcs(),
+                    )
+                },
+            )
+            .await?;
+        let object_ref = res
+            .data
+            .ok_or_else(|| anyhow!("dWallet object {} not found", object_id))?
+            .object_ref();
+        Ok(object_ref)
+    }
+}
@@ -0,0 +1,629 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use move_core_types::account_address::AccountAddress;
+use sui_config::{sui_config_dir, SUI_KEYSTORE_FILENAME};
+use sui_keys::keystore::FileBasedKeystore;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    digests::CheckpointDigest,
+    SUI_SYSTEM_PACKAGE_ID,
+};
+use tracing::info;
+use url::Url;
+
+/// Version of the config file layout written by this build. Files of an older version are
+/// upgraded on load by [`Config::load`].
+pub const CONFIG_VERSION: u32 = 1;
+
+/// A Sui network with well-known public endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+}
+
+impl Network {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+        }
+    }
+
+    pub fn full_node_url(&self) -> String {
+        format!("https://fullnode.{}.sui.io:443", self.name())
+    }
+
+    pub fn object_store_url(&self) -> String {
+        format!("https://checkpoints.{}.sui.io", self.name())
+    }
+
+    pub fn graphql_url(&self) -> String {
+        format!("https://sui-{}.mystenlabs.com/graphql", self.name())
+    }
+
+    /// The chain identifier of the network, derived from its genesis checkpoint, if it is
+    /// never reset. Devnet is reset regularly so it has none.
+    pub fn chain_identifier(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some("35834a8a"),
+            Network::Testnet => Some("4c78adac"),
+            Network::Devnet => None,
+        }
+    }
+}
+
+/// How the last checkpoint of an epoch is looked up while syncing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpochLookup {
+    /// A single query to the GraphQL endpoint
+    #[default]
+    Graphql,
+    /// A binary search over checkpoint summaries in the object store, for networks
+    /// without a GraphQL endpoint
+    BinarySearch,
+}
+
+/// Where full checkpoints and checkpoint summaries are downloaded from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointSource {
+    /// The object store at `object_store_url`, then its fallback stores
+    #[default]
+    ObjectStore,
+    /// The REST API of the full node at `sui_full_node_url`, e.g. an operator's own node,
+    /// leaving the object store unused
+    FullNode,
+}
+
+// The config file for the light client including the root of trust genesis digest
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Config {
+    /// Version of the file layout, 0 for files written before it was recorded
+    #[serde(default)]
+    pub config_version: u32,
+
+    /// Network whose public endpoints are used for the urls left empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<Network>,
+
+    /// Full node url
+    #[serde(default)]
+    pub sui_full_node_url: String,
+
+    pub dwallet_full_node_url: String,
+
+    /// Checkpoint summary directory
+    pub checkpoint_summary_dir: PathBuf,
+
+    /// Genesis file name in the checkpoint directory, `genesis_<network>.blob` of the
+    /// network preset if unset
+    #[serde(default)]
+    pub genesis_filename: PathBuf,
+
+    /// Digest of the genesis checkpoint the genesis file must contain, to catch a genesis
+    /// file of another network
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genesis_digest: Option<CheckpointDigest>,
+
+    /// The epoch whose end-of-epoch checkpoint the genesis committee signs, 0 unless the
+    /// network started its checkpoints at a later epoch
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub genesis_committee_epoch: u64,
+
+    /// Object store url
+    #[serde(default)]
+    pub object_store_url: String,
+
+    /// Object stores tried in order for a checkpoint missing from `object_store_url`, e.g.
+    /// an archive keeping the checkpoints pruned from it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub object_store_fallback_urls: Vec<String>,
+
+    /// Where checkpoints are downloaded from, `object_store` or `full_node`
+    #[serde(default)]
+    pub checkpoint_source: CheckpointSource,
+
+    /// GraphQL endpoint
+    #[serde(default)]
+    pub graphql_url: String,
+
+    /// How the last checkpoint of each epoch is looked up, `graphql` or `binary_search`
+    #[serde(default)]
+    pub epoch_lookup: EpochLookup,
+
+    /// Sui deployed state proof package
+    #[serde(default)]
+    pub sui_deployed_state_proof_package: String,
+
+    /// Dwallet registry object id, left empty until written by init
+    #[serde(default)]
+    pub dwltn_registry_object_id: String,
+
+    /// Dwallet config object id, left empty until written by init
+    #[serde(default)]
+    pub dwltn_config_object_id: String,
+
+    /// Initial shared version of the registry, recorded by init and checked against the
+    /// registry on the first submission of a sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dwltn_registry_initial_shared_version: Option<u64>,
+
+    /// Dwallet state proof package id
+    #[serde(default = "default_state_proof_package_id")]
+    pub dwltn_state_proof_package_id: ObjectID,
+
+    /// Path of a checkpoint in the object store, with `{seq}` replaced by its sequence
+    /// number, zero-padded to N digits for `{seq:N}`, and `{epoch}` by its epoch
+    #[serde(default = "default_object_store_path_template")]
+    pub object_store_path_template: String,
+
+    /// Options of the object store backend picked from the url scheme, e.g.
+    /// `aws_access_key_id`, `aws_region` or `aws_endpoint` for S3-compatible stores.
+    /// Access is anonymous if none are given
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub object_store_options: BTreeMap<String, String>,
+
+    /// Most object store requests in flight at once, across all downloads
+    #[serde(default = "default_object_store_max_concurrency")]
+    pub object_store_max_concurrency: usize,
+
+    /// Most object store requests started per second, unlimited if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_store_rps: Option<u32>,
+
+    /// Number of concurrent GraphQL queries while syncing the checkpoint list
+    #[serde(default = "default_sync_concurrency")]
+    pub sync_concurrency: usize,
+
+    /// Number of full checkpoints downloaded concurrently when verifying many transactions
+    #[serde(default = "default_prefetch_concurrency")]
+    pub prefetch_concurrency: usize,
+
+    /// Most Move packages cached by the package store resolving types, evicting the least
+    /// recently used one past it, unbounded if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cache_entries: Option<usize>,
+
+    /// Most full checkpoints held in memory at once when verifying many transactions
+    #[serde(default = "default_prefetch_max_checkpoints")]
+    pub prefetch_max_checkpoints: usize,
+
+    /// Timeout in seconds for each full node, dWallet, GraphQL and object store request
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+
+    /// Time in seconds a dWallet full node request is retried for before failing
+    #[serde(default = "default_dwallet_retry_budget_secs")]
+    pub dwallet_retry_budget_secs: u64,
+
+    /// Consecutive failed dWallet full node requests after which requests fail fast
+    #[serde(default = "default_dwallet_breaker_threshold")]
+    pub dwallet_breaker_threshold: u32,
+
+    /// Gas station sponsoring the dWallet submissions, instead of the keystore coins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_station_url: Option<String>,
+
+    /// Margin in percent added to the estimated gas cost of dWallet transactions
+    #[serde(default = "default_gas_budget_margin_percent")]
+    pub gas_budget_margin_percent: u64,
+
+    /// Keystore signing the dWallet transactions, `sui.keystore` in the Sui config
+    /// directory if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystore_path: Option<PathBuf>,
+
+    /// Keystore address signing the dWallet transactions, the first key if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dwltn_sender_address: Option<SuiAddress>,
+
+    /// PEM root certificate trusted in addition to the system ones, e.g. of a proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_ca_cert_path: Option<PathBuf>,
+
+    /// Address serving Prometheus metrics at `/metrics`, none are served if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Verify from the checkpoint directory only, failing any network access
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+fn default_dwallet_retry_budget_secs() -> u64 {
+    60
+}
+
+fn default_dwallet_breaker_threshold() -> u32 {
+    5
+}
+
+fn parse_dwallet_object_id(name: &str, id: &str) -> anyhow::Result<ObjectID> {
+    anyhow::ensure!(!id.is_empty(), "{} is not set, run init first", name);
+    ObjectID::from_hex_literal(id).with_context(|| format!("{}: invalid object id {:?}", name, id))
+}
+
+fn default_object_store_path_template() -> String {
+    "{seq}.chk".to_string()
+}
+
+fn default_object_store_max_concurrency() -> usize {
+    16
+}
+
+fn default_sync_concurrency() -> usize {
+    16
+}
+
+/// Reads a proxy url from `var`, or its lowercase form as curl does
+fn proxy_from_env(var: &str) -> Option<String> {
+    [var.to_string(), var.to_lowercase()]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|url| !url.is_empty()))
+}
+
+fn default_gas_budget_margin_percent() -> u64 {
+    20
+}
+
+fn default_prefetch_concurrency() -> usize {
+    4
+}
+
+fn default_prefetch_max_checkpoints() -> usize {
+    8
+}
+
+fn default_state_proof_package_id() -> ObjectID {
+    SUI_SYSTEM_PACKAGE_ID
+}
+
+impl Config {
+    /// Reads the config file at `path`. A file of an older `config_version` is upgraded in
+    /// place: the fields it lacks are written with their defaults and logged.
+    pub fn load(path: &Path) -> anyhow::Result<Config> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to load config from {}", path.display()))?;
+        let value: serde_yaml::Value = serde_yaml::from_str(&contents)
+            .with_context(|| format!("Invalid config in {}", path.display()))?;
+        let (config, added) = Config::migrate(value)
+            .with_context(|| format!("Invalid config in {}", path.display()))?;
+        if let Some(added) = added {
+            info!(
+                path = %path.display(),
+                version = CONFIG_VERSION,
+                added = ?added,
+                "Upgrading config file"
+            );
+            fs::write(path, serde_yaml::to_string(&config)?)
+                .with_context(|| format!("Unable to upgrade config file {}", path.display()))?;
+        }
+        Ok(config)
+    }
+
+    /// Parses a config file, upgrading it to [`CONFIG_VERSION`] if it is older. Returns the
+    /// fields added with their defaults by the upgrade, `None` if it was up to date.
+    pub fn migrate(value: serde_yaml::Value) -> anyhow::Result<(Config, Option<Vec<String>>)> {
+        let mut config: Config = serde_yaml::from_value(value.clone())?;
+        anyhow::ensure!(
+            config.config_version <= CONFIG_VERSION,
+            "config_version {} is newer than the {} supported by this build",
+            config.config_version,
+            CONFIG_VERSION
+        );
+        if config.config_version == CONFIG_VERSION {
+            return Ok((config, None));
+        }
+
+        config.config_version = CONFIG_VERSION;
+        let upgraded = serde_yaml::to_value(&config)?;
+        let added = match (upgraded.as_mapping(), value.as_mapping()) {
+            (Some(upgraded), Some(original)) => upgraded
+                .iter()
+                .map(|(key, _)| key)
+                .filter(|key| !original.contains_key(key))
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect(),
+            _ => vec![],
+        };
+        Ok((config, Some(added)))
+    }
+
+    /// Fills the Sui endpoint urls left empty from the network preset, failing if one
+    /// is still missing.
+    pub fn apply_network_defaults(&mut self) -> anyhow::Result<()> {
+        if let Some(network) = self.network {
+            for (url, default) in [
+                (&mut self.sui_full_node_url, network.full_node_url()),
+                (&mut self.object_store_url, network.object_store_url()),
+                (&mut self.graphql_url, network.graphql_url()),
+            ] {
+                if url.is_empty() {
+                    *url = default;
+                }
+            }
+            if self.genesis_filename.as_os_str().is_empty() {
+                self.genesis_filename = format!("genesis_{}.blob", network.name()).into();
+            }
+        }
+        anyhow::ensure!(
+            !self.genesis_filename.as_os_str().is_empty(),
+            "genesis_filename is not set and no network preset is given"
+        );
+
+        for (name, url) in [
+            ("sui_full_node_url", &self.sui_full_node_url),
+            ("object_store_url", &self.object_store_url),
+            ("graphql_url", &self.graphql_url),
+        ] {
+            if self.is_unused_url(name) {
+                continue;
+            }
+            anyhow::ensure!(
+                !url.is_empty(),
+                "{} is not set and no network preset is given",
+                name
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that the urls and object ids parse and that the checkpoint directory exists
+    /// or can be created, reporting every invalid field at once.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+
+        // The Sui endpoints may be left empty for the network preset to fill in, the GraphQL
+        // one is unused by the binary search epoch lookup and the object store one when
+        // checkpoints come from the full node
+        for (name, url) in [
+            ("sui_full_node_url", &self.sui_full_node_url),
+            ("object_store_url", &self.object_store_url),
+            ("graphql_url", &self.graphql_url),
+        ] {
+            if url.is_empty() && (self.network.is_some() || self.is_unused_url(name)) {
+                continue;
+            }
+            if url.is_empty() {
+                errors.push(format!("{}: not set and no network preset is given", name));
+            } else if name == "object_store_url" {
+                if let Err(e) = self.check_object_store_url() {
+                    errors.push(e.to_string());
+                }
+            } else if let Err(e) = Url::parse(url) {
+                errors.push(format!("{}: invalid url {:?}: {}", name, url, e));
+            }
+        }
+        let mut urls = vec![("dwallet_full_node_url", &self.dwallet_full_node_url)];
+        if let Some(url) = &self.gas_station_url {
+            urls.push(("gas_station_url", url));
+        }
+        for url in &self.object_store_fallback_urls {
+            urls.push(("object_store_fallback_urls", url));
+        }
+        for (name, url) in urls {
+            if let Err(e) = Url::parse(url) {
+                errors.push(format!("{}: invalid url {:?}: {}", name, url, e));
+            }
+        }
+
+        if let Err(e) = AccountAddress::from_hex_literal(&self.sui_deployed_state_proof_package) {
+            errors.push(format!(
+                "sui_deployed_state_proof_package: invalid address {:?}: {}",
+                self.sui_deployed_state_proof_package, e
+            ));
+        }
+        for (name, id) in [
+            ("dwltn_registry_object_id", &self.dwltn_registry_object_id),
+            ("dwltn_config_object_id", &self.dwltn_config_object_id),
+        ] {
+            // Unset until init creates the objects, checked by the commands using them
+            if id.is_empty() {
+                continue;
+            }
+            if let Err(e) = ObjectID::from_hex_literal(id) {
+                errors.push(format!("{}: invalid object id {:?}: {}", name, id, e));
+            }
+        }
+
+        if let Err(e) = self.checkpoint_object_path(0, Some(0)) {
+            errors.push(format!("object_store_path_template: {}", e));
+        }
+        if self.object_store_max_concurrency == 0 {
+            errors.push("object_store_max_concurrency: must be at least 1".to_string());
+        }
+        if self.object_store_rps == Some(0) {
+            errors.push("object_store_rps: must be at least 1, or unset".to_string());
+        }
+        if self.max_cache_entries == Some(0) {
+            errors.push("max_cache_entries: must be at least 1, or unset".to_string());
+        }
+
+        if let Some(path) = &self.extra_ca_cert_path {
+            if let Err(e) = std::fs::metadata(path) {
+                errors.push(format!(
+                    "extra_ca_cert_path: cannot read {}: {}",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+
+        if let Some(path) = &self.keystore_path {
+            if !path.is_file() {
+                errors.push(format!("keystore_path: {} not found", path.display()));
+            }
+        }
+
+        if self.genesis_filename.as_os_str().is_empty() && self.network.is_none() {
+            errors.push("genesis_filename: not set and no network preset is given".to_string());
+        }
+
+        if let Err(e) = std::fs::create_dir_all(&self.checkpoint_summary_dir) {
+            errors.push(format!(
+                "checkpoint_summary_dir: cannot create {}: {}",
+                self.checkpoint_summary_dir.display(),
+                e
+            ));
+        }
+
+        anyhow::ensure!(
+            errors.is_empty(),
+            "{} invalid config field(s):\n  {}",
+            errors.len(),
+            errors.join("\n  ")
+        );
+        Ok(())
+    }
+
+    /// Checks that `object_store_url` has a scheme with an object store backend, so a
+    /// malformed url fails here rather than deep in a sync.
+    pub fn check_object_store_url(&self) -> anyhow::Result<()> {
+        let scheme = Url::parse(&self.object_store_url).map(|url| url.scheme().to_string());
+        anyhow::ensure!(
+            matches!(scheme.as_deref(), Ok("https" | "s3" | "gs" | "file")),
+            "object_store_url must be one of https://, s3://, gs://, file://; got '{}'",
+            self.object_store_url
+        );
+        Ok(())
+    }
+
+    /// Whether the Sui endpoint `name` is never requested with this config, so it may be
+    /// left unset
+    fn is_unused_url(&self, name: &str) -> bool {
+        match name {
+            "graphql_url" => self.epoch_lookup == EpochLookup::BinarySearch,
+            "object_store_url" => self.checkpoint_source == CheckpointSource::FullNode,
+            _ => false,
+        }
+    }
+
+    pub fn sui_rest_url(&self) -> String {
+        format!("{}/rest", self.sui_full_node_url)
+    }
+
+    pub fn dwallet_full_node_url(&self) -> String {
+        format!("{}", self.dwallet_full_node_url)
+    }
+
+    /// The dWallet registry object id, failing if init has not set it yet
+    pub fn registry_object_id(&self) -> anyhow::Result<ObjectID> {
+        parse_dwallet_object_id("dwltn_registry_object_id", &self.dwltn_registry_object_id)
+    }
+
+    /// The dWallet state proof config object id, failing if init has not set it yet
+    pub fn state_proof_config_object_id(&self) -> anyhow::Result<ObjectID> {
+        parse_dwallet_object_id("dwltn_config_object_id", &self.dwltn_config_object_id)
+    }
+
+    /// The gas station url from the config, or the `DWALLET_GAS_STATION_URL` env var
+    pub fn gas_station_url(&self) -> Option<String> {
+        self.gas_station_url
+            .clone()
+            .or_else(|| std::env::var("DWALLET_GAS_STATION_URL").ok())
+    }
+
+    /// Path of checkpoint `seq` in the object store, from `object_store_path_template`.
+    /// Fails if the template uses `{epoch}` and the epoch of the checkpoint is not known.
+    pub fn checkpoint_object_path(&self, seq: u64, epoch: Option<u64>) -> anyhow::Result<String> {
+        let mut path = String::new();
+        let mut rest = self.object_store_path_template.as_str();
+        while let Some(start) = rest.find('{') {
+            path.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or_else(|| anyhow::anyhow!("unclosed '{{' in {:?}", rest))?;
+            let placeholder = &rest[start + 1..end];
+            let (name, width) = match placeholder.split_once(':') {
+                Some((name, width)) => (
+                    name,
+                    width
+                        .parse::<usize>()
+                        .with_context(|| format!("invalid width in {{{}}}", placeholder))?,
+                ),
+                None => (placeholder, 0),
+            };
+            let value = match name {
+                "seq" => seq,
+                "epoch" => epoch.ok_or_else(|| {
+                    anyhow::anyhow!("the epoch of checkpoint {} is not known for {{epoch}}", seq)
+                })?,
+                _ => anyhow::bail!("unknown placeholder {{{}}}", placeholder),
+            };
+            path.push_str(&format!("{:0width$}", value, width = width));
+            rest = &rest[end + 1..];
+        }
+        path.push_str(rest);
+        Ok(path)
+    }
+
+    /// The proxy for plain http connections, from the `HTTP_PROXY` env var
+    pub fn http_proxy(&self) -> Option<String> {
+        proxy_from_env("HTTP_PROXY")
+    }
+
+    /// The proxy for https connections, from the `HTTPS_PROXY` env var
+    pub fn https_proxy(&self) -> Option<String> {
+        proxy_from_env("HTTPS_PROXY")
+    }
+
+    /// The PEM contents of `extra_ca_cert_path`, if set
+    pub fn extra_ca_cert(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        self.extra_ca_cert_path
+            .as_ref()
+            .map(|path| {
+                std::fs::read(path)
+                    .with_context(|| format!("Unable to read CA certificate {}", path.display()))
+            })
+            .transpose()
+    }
+
+    /// The keystore at `keystore_path`, or at the default Sui config location if unset
+    pub fn keystore(&self) -> anyhow::Result<FileBasedKeystore> {
+        let path = match &self.keystore_path {
+            Some(path) => path.clone(),
+            None => sui_config_dir()
+                .context("Unable to locate the Sui config directory")?
+                .join(SUI_KEYSTORE_FILENAME),
+        };
+        anyhow::ensure!(
+            path.is_file(),
+            "Keystore {} not found, set keystore_path in the config",
+            path.display()
+        );
+        FileBasedKeystore::new(&path)
+            .with_context(|| format!("Unable to open keystore {}", path.display()))
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    pub fn dwallet_retry_budget(&self) -> Duration {
+        Duration::from_secs(self.dwallet_retry_budget_secs)
+    }
+}
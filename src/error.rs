@@ -0,0 +1,76 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use sui_types::{digests::TransactionDigest, error::SuiError};
+
+use crate::verifier::SignatureDiagnostic;
+
+/// The failures of the light client a caller may want to handle, e.g. syncing on
+/// [`LightClientError::NeedsSync`].
+///
+/// The [`LightClient`](crate::LightClient) methods return them inside an `anyhow::Error`,
+/// possibly with context added, so they are matched with `downcast_ref`.
+#[derive(Debug, thiserror::Error)]
+pub enum LightClientError {
+    #[error("Checkpoint {0} not found")]
+    CheckpointNotFound(u64),
+
+    /// A checkpoint certificate did not verify against the committee
+    #[error("{diagnostic}")]
+    InvalidSignature {
+        diagnostic: SignatureDiagnostic,
+        #[source]
+        source: Box<SuiError>,
+    },
+
+    #[error("{0}")]
+    VerificationFailed(String),
+
+    #[error("No committee stored for epoch {epoch}")]
+    CommitteeMissing { epoch: u64 },
+
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+
+    #[error("Transaction {0} not found")]
+    TransactionNotFound(TransactionDigest),
+
+    /// The transaction is executed but not included in a checkpoint yet, so it can be
+    /// retried later
+    #[error("Transaction {0} is not in a checkpoint yet")]
+    TransactionPending(TransactionDigest),
+
+    /// The dWallet full node failed `failures` requests in a row, so requests to it fail
+    /// fast until it recovers
+    #[error("dWallet network unavailable after {failures} consecutive failed requests")]
+    DwalletUnavailable { failures: u32 },
+
+    /// The full node and the object store serve different chains, e.g. a mainnet full node
+    /// with a testnet object store
+    #[error("Full node is on chain {full_node} but the object store serves chain {object_store}")]
+    ChainMismatch {
+        full_node: String,
+        object_store: String,
+    },
+
+    /// The genesis file is not for the chain the light client targets, e.g. a testnet
+    /// genesis in a mainnet config
+    #[error("genesis digest mismatch — wrong network? The genesis file is for chain {genesis} but {expected_by} is on chain {expected}")]
+    GenesisMismatch {
+        genesis: String,
+        expected: String,
+        expected_by: String,
+    },
+
+    /// No committee of the epoch is registered in the dWallet registry, e.g. because the
+    /// sync has not submitted it yet
+    #[error("No committee of epoch {epoch} is registered in the dWallet registry")]
+    EpochCommitteeNotRegistered { epoch: u64 },
+
+    #[error("RPC error: {0}")]
+    Rpc(String),
+
+    /// The stored end-of-epoch checkpoints do not reach the epoch needed
+    #[error("Stored checkpoints reach epoch {have_epoch} but epoch {want_epoch} is needed. Need to Sync.")]
+    NeedsSync { have_epoch: u64, want_epoch: u64 },
+}
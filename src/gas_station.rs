@@ -0,0 +1,149 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use anyhow::{anyhow, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sui_json_rpc_types::{SuiObjectRef, SuiTransactionBlockEffects};
+use sui_types::{
+    base_types::{ObjectRef, SuiAddress},
+    crypto::Signature,
+    transaction::TransactionData,
+};
+
+/// Environment variable holding the bearer token sent to the gas station
+pub const GAS_STATION_AUTH_ENV: &str = "DWALLET_GAS_STATION_AUTH";
+
+#[derive(Serialize)]
+struct ReserveGasRequest {
+    gas_budget: u64,
+    reserve_duration_secs: u64,
+}
+
+#[derive(Deserialize)]
+struct ReserveGasResponse {
+    result: Option<ReserveGasResult>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReserveGasResult {
+    sponsor_address: SuiAddress,
+    reservation_id: u64,
+    gas_coins: Vec<SuiObjectRef>,
+}
+
+#[derive(Serialize)]
+struct ExecuteTxRequest {
+    reservation_id: u64,
+    tx_bytes: String,
+    user_sig: String,
+}
+
+#[derive(Deserialize)]
+struct ExecuteTxResponse {
+    effects: Option<SuiTransactionBlockEffects>,
+    error: Option<String>,
+}
+
+/// Gas coins the gas station reserved for a single transaction.
+pub struct GasReservation {
+    pub sponsor: SuiAddress,
+    pub reservation_id: u64,
+    pub gas_coins: Vec<ObjectRef>,
+}
+
+/// Client for a gas station sponsoring the transactions sent to the dWallet network.
+pub struct GasStationClient {
+    url: String,
+    auth: Option<String>,
+    client: reqwest::Client,
+}
+
+impl GasStationClient {
+    pub fn new(url: &str, client: reqwest::Client) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            auth: std::env::var(GAS_STATION_AUTH_ENV).ok(),
+            client,
+        }
+    }
+
+    async fn post<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        path: &str,
+        request: &Req,
+    ) -> anyhow::Result<Resp> {
+        let mut builder = self
+            .client
+            .post(format!("{}/{}", self.url, path))
+            .json(request);
+        if let Some(auth) = &self.auth {
+            builder = builder.bearer_auth(auth);
+        }
+
+        // Failed requests still carry the reason in the response body
+        builder
+            .send()
+            .await
+            .with_context(|| format!("Cannot connect to gas station at {}", self.url))?
+            .json::<Resp>()
+            .await
+            .with_context(|| format!("Incorrect response from gas station {}", path))
+    }
+
+    /// Reserves gas coins covering `gas_budget` for `reserve_duration_secs`.
+    pub async fn reserve_gas(
+        &self,
+        gas_budget: u64,
+        reserve_duration_secs: u64,
+    ) -> anyhow::Result<GasReservation> {
+        let request = ReserveGasRequest {
+            gas_budget,
+            reserve_duration_secs,
+        };
+        let response: ReserveGasResponse = self.post("v1/reserve_gas", &request).await?;
+        if let Some(error) = response.error {
+            return Err(anyhow!("Gas station failed to reserve gas: {}", error));
+        }
+
+        let result = response
+            .result
+            .ok_or(anyhow!("Gas station returned no reservation"))?;
+        Ok(GasReservation {
+            sponsor: result.sponsor_address,
+            reservation_id: result.reservation_id,
+            gas_coins: result
+                .gas_coins
+                .iter()
+                .map(SuiObjectRef::to_object_ref)
+                .collect(),
+        })
+    }
+
+    /// Executes a transaction paid for with the coins of reservation `reservation_id`,
+    /// co-signed by the gas station.
+    pub async fn execute_tx(
+        &self,
+        reservation_id: u64,
+        tx_data: &TransactionData,
+        user_sig: &Signature,
+    ) -> anyhow::Result<SuiTransactionBlockEffects> {
+        let request = ExecuteTxRequest {
+            reservation_id,
+            tx_bytes: Base64::encode(bcs::to_bytes(tx_data)?),
+            user_sig: Base64::encode(user_sig.as_ref()),
+        };
+        let response: ExecuteTxResponse = self.post("v1/execute_tx", &request).await?;
+        if let Some(error) = response.error {
+            return Err(anyhow!(
+                "Gas station failed to execute transaction: {}",
+                error
+            ));
+        }
+
+        response
+            .effects
+            .ok_or(anyhow!("Gas station returned no transaction effects"))
+    }
+}
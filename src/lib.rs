@@ -0,0 +1,46 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! A light client for the Sui blockchain that syncs end-of-epoch checkpoints to the
+//! dWallet network and verifies transactions and objects against them.
+//!
+//! [`LightClient`] is the entry point: it owns the [`Config`] and connections, syncs the
+//! committee chain from [`genesis_committee`] and verifies transactions and objects
+//! against it. The verification steps are also exported on their own, for checkpoints
+//! obtained elsewhere, along with [`TransactionProof`] and [`ProofBundle`] to check a
+//! transaction offline later.
+
+mod bundle;
+mod checkpoint_store;
+mod circuit_breaker;
+mod client;
+mod config;
+mod error;
+mod gas_station;
+mod limiter;
+mod metrics;
+mod network_stub;
+mod package_store;
+mod proof;
+mod verifier;
+
+pub use bundle::ProofBundle;
+pub use circuit_breaker::CircuitState;
+pub use client::{
+    decode_checkpoint_blob, estimate_gas_budget, find_created_object, genesis_committee,
+    select_gas_coin, select_sender, CheckpointsList, LightClient, ProofSubmission, SyncOptions,
+    SyncState, VerifiedTransactionBlock,
+};
+pub use config::{CheckpointSource, Config, EpochLookup, Network, CONFIG_VERSION};
+pub use error::LightClientError;
+pub use network_stub::NetworkStub;
+pub use package_store::{CachingResolver, PackageCacheStats, RemotePackageStore};
+pub use proof::{build_transaction_proof, verify_transaction_proof, TransactionProof};
+pub use verifier::{
+    check_contiguous_epochs, classify_object_changes, committee_from_stakes, diff_committees,
+    extract_verified_effects_and_events, extract_verified_sender_and_gas,
+    extract_verified_transaction_data, next_committee_from_summary,
+    next_protocol_version_from_summary, verify_checkpoint_summary, verify_checkpoint_transactions,
+    verify_end_of_epoch_summary, ChangedObject, CommitteeChange, ObjectChangeSummary,
+    SignatureDiagnostic, VerifiedSenderAndGas, VerifiedTransaction,
+};
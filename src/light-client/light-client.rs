@@ -1,59 +1,49 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: BSD-3-Clause-Clear
 
-use anyhow::{anyhow, Context, Result};
-use async_trait::async_trait;
+use anyhow::{anyhow, Context};
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use clap::{Parser, Subcommand, ValueEnum};
+use fastcrypto::encoding::{Base64, Encoding, Hex};
+use move_core_types::language_storage::{StructTag, TypeTag};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
-use object_store::path::Path;
-use object_store::ObjectStore;
-use sui_json_rpc_types::{
-    SuiEvent, SuiObjectDataOptions, SuiTransactionBlockResponseOptions,
+use serde_json::json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-
-use sui_json_rpc_types::{EventFilter, ObjectChange};
-
-use sui_rest_api::{CheckpointData, Client};
-use sui_types::transaction::ObjectArg;
-use sui_types::{
-    base_types::{ObjectID, ObjectRef},
-    committee::Committee,
-    crypto::AuthorityQuorumSignInfo,
-    message_envelope::Envelope,
-    messages_checkpoint::{CertifiedCheckpointSummary, CheckpointSummary, EndOfEpochData},
-    object::{Object, Owner},
+use sui_json::SuiJsonValue;
+use sui_json_rpc_types::{SuiEvent, SuiExecutionStatus, SuiTransactionBlockEffectsAPI};
+use sui_light_client::{
+    classify_object_changes, committee_from_stakes, diff_committees, find_created_object,
+    next_committee_from_summary, verify_end_of_epoch_summary, CachingResolver, CheckpointSource,
+    Config, EpochLookup, LightClient, LightClientError, ProofBundle, RemotePackageStore,
+    SyncOptions,
 };
-
-use sui_config::genesis::Genesis;
-
-use sui_package_resolver::Result as ResolverResult;
-use sui_package_resolver::{Package, PackageStore, Resolver};
-use sui_sdk::{SuiClientBuilder};
-
-use clap::{Parser, Subcommand};
-use std::thread::sleep;
-use std::{fs, io::Write, path::PathBuf, str::FromStr};
-use std::{io::Read, sync::Arc};
-
-use sui_config::{sui_config_dir, SUI_KEYSTORE_FILENAME};
-use move_core_types::language_storage::{StructTag, TypeTag};
-use serde_json::{Value};
-use shared_crypto::intent::Intent;
-use sui_keys::keystore::{AccountKeystore, FileBasedKeystore};
-use sui_sdk::{
-    types::{
-        programmable_transaction_builder::ProgrammableTransactionBuilder,
-        quorum_driver_types::ExecuteTransactionRequestType,
-        transaction::{Argument, Command, ProgrammableMoveCall, Transaction, TransactionData},
+use sui_sdk::types::{
+    programmable_transaction_builder::ProgrammableTransactionBuilder,
+    transaction::{
+        CallArg, Command, ObjectArg, ProgrammableMoveCall, TransactionData, TransactionDataAPI,
+        TransactionKind,
     },
 };
-use std::{collections::HashMap, sync::Mutex};
-
-use log::info;
-use object_store::parse_url;
-use serde_json::json;
-use url::Url;
-
+use sui_types::{
+    base_types::{ObjectID, SequenceNumber},
+    committee::Committee,
+    digests::TransactionDigest,
+    effects::{TransactionEffects, TransactionEffectsAPI, TransactionEvents},
+    event::Event,
+    messages_checkpoint::CertifiedCheckpointSummary,
+    object::{Data, MoveObject, Object},
+    parse_sui_struct_tag, parse_sui_type_tag,
+};
+use tracing::{debug, info, warn};
 
+#[cfg(feature = "server")]
+mod server;
 
 /// A light client for the Sui blockchain
 #[derive(Parser, Debug)]
@@ -63,908 +53,1662 @@ struct Args {
     #[arg(short, long, value_name = "FILE")]
     config: Option<PathBuf>,
 
-    #[command(subcommand)]
-    command: Option<SCommands>,
-}
-
-struct RemotePackageStore {
-    config: Config,
-    cache: Mutex<HashMap<AccountAddress, Arc<Package>>>,
-}
-impl RemotePackageStore {
-    pub fn new(config: Config) -> Self {
-        Self {
-            config,
-            cache: Mutex::new(HashMap::new()),
-        }
-    }
-}
-
-#[async_trait]
-impl PackageStore for RemotePackageStore {
-    /// Read package contents. Fails if `id` is not an object, not a package, or is malformed in
-    /// some way.
-    async fn fetch(&self, id: AccountAddress) -> ResolverResult<Arc<Package>> {
-        // Check if we have it in the cache
-        if let Some(package) = self.cache.lock().unwrap().get(&id) {
-            // info!("Fetch Package: {} cache hit", id);
-            return Ok(package.clone());
-        }
-
-        info!("Fetch Package: {}", id);
-
-        let object: Object = get_verified_object(&self.config, id.into()).await.unwrap();
-        let package = Arc::new(Package::read_from_object(&object).unwrap());
+    /// Verify from the checkpoint directory only, without network access
+    #[arg(long)]
+    offline: bool,
 
-        // Add to the cache
-        self.cache.lock().unwrap().insert(id, package.clone());
+    /// Print the endpoints in use and the chain they serve before running the command
+    #[arg(short, long)]
+    verbose: bool,
 
-        Ok(package)
-    }
+    #[command(subcommand)]
+    command: Option<SCommands>,
 }
 
 #[derive(Subcommand, Debug)]
 enum SCommands {
     /// Sync all end-of-epoch checkpoints
     Init {
-        #[arg(short, long, value_name = "TID")]
-        ckp_id: u64,
+        #[arg(
+            short,
+            long,
+            value_name = "TID",
+            required_unless_present = "since_epoch"
+        )]
+        ckp_id: Option<u64>,
+
+        /// Initialize from the last checkpoint of this epoch instead of a checkpoint id,
+        /// verified against the committee chain from genesis
+        #[arg(long, value_name = "EPOCH", conflicts_with = "ckp_id")]
+        since_epoch: Option<u64>,
+    },
+
+    /// Syncs checkpoints and submits new committees to the dWallet network
+    Sync {
+        /// Build the first pending submission and log it instead of executing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Hide the progress bar
+        #[arg(long)]
+        no_progress: bool,
+
+        /// Process the checkpoints before one that fails to download, then report it
+        #[arg(long)]
+        continue_on_error: bool,
     },
 
-    Sync {},
+    /// Syncs new epochs continuously, every interval, until interrupted
+    Watch {
+        /// Seconds to wait between two syncs
+        #[arg(long, value_name = "SECS", default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+        interval_secs: u64,
+    },
 
     /// Checks a specific transaction using the light client
     Transaction {
         /// Transaction hash
         #[arg(short, long, value_name = "TID")]
         tid: String,
+
+        /// Output format of the verification result
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Checkpoint including the transaction, required offline
+        #[arg(long, value_name = "SEQ")]
+        checkpoint: Option<u64>,
+
+        /// Wait up to this many seconds for a pending transaction to be checkpointed
+        #[arg(long, value_name = "SECS", conflicts_with = "checkpoint")]
+        wait: Option<u64>,
+
+        /// BCS committee file to verify against, instead of the stored checkpoints
+        #[arg(long, value_name = "FILE")]
+        committee_file: Option<PathBuf>,
     },
-}
 
+    /// Lists the events of a Move type emitted in a verified checkpoint
+    Events {
+        /// Checkpoint sequence number
+        #[arg(short, long, value_name = "SEQ")]
+        ckp: u64,
 
-// The config file for the light client including the root of trust genesis digest
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-struct Config {
-    /// Full node url
-    sui_full_node_url: String,
+        /// Event type, e.g. 0x3::validator::StakingRequestEvent, matching any type
+        /// arguments if none are given
+        #[arg(long, value_name = "TYPE")]
+        event_type: String,
 
-    dwallet_full_node_url: String,
+        /// Output format of the events
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
 
-    /// Checkpoint summary directory
-    checkpoint_summary_dir: PathBuf,
+    /// Verifies transactions and submits their state proofs to the dWallet network in one
+    /// transaction
+    SubmitProofs {
+        /// Transaction hash, repeated for each transaction
+        #[arg(short, long = "tid", value_name = "TID", required = true)]
+        tids: Vec<String>,
 
-    //  Genesis file name
-    genesis_filename: PathBuf,
+        /// Build the submission and log it instead of executing it
+        #[arg(long)]
+        dry_run: bool,
+    },
 
-    /// Object store url
-    object_store_url: String,
+    /// Serves the verification of transactions and objects as JSON over HTTP
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:6921")]
+        addr: std::net::SocketAddr,
+    },
 
-    /// GraphQL endpoint
-    graphql_url: String,
+    /// Fetches and verifies an object using the light client
+    Object {
+        /// Object id
+        #[arg(short, long, value_name = "OID")]
+        id: String,
 
-    /// Sui deployed state proof package
-    sui_deployed_state_proof_package: String,
+        /// Verify the object as it was at this version instead of the latest one
+        #[arg(long, value_name = "VERSION")]
+        version: Option<u64>,
+    },
 
-    /// Dwallet registry object id
-    dwltn_registry_object_id: String,
+    /// Fetches and verifies a dynamic field of an object, e.g. a table entry
+    DynamicField {
+        /// Object id of the parent, e.g. the table
+        #[arg(short, long, value_name = "OID")]
+        parent: String,
 
-    /// Dwallet config object id
-    dwltn_config_object_id: String,
+        /// Move type of the field name, e.g. u64 or 0x1::string::String
+        #[arg(long, value_name = "TYPE")]
+        name_type: String,
+
+        /// Field name as JSON, e.g. 42 or "\"key\""
+        #[arg(long, value_name = "JSON")]
+        name_json: String,
+    },
+
+    /// Re-verifies a stored checkpoint offline
+    Verify {
+        /// Checkpoint sequence number
+        #[arg(short, long, value_name = "SEQ")]
+        ckp_id: u64,
+
+        /// BCS committee file to verify against, downloading the checkpoint if not stored
+        #[arg(long, value_name = "FILE")]
+        committee_file: Option<PathBuf>,
+    },
+
+    /// Downloads, verifies and stores any checkpoint, outside the end-of-epoch list
+    Cache {
+        /// Checkpoint sequence number
+        #[arg(short, long, value_name = "SEQ")]
+        ckp_id: u64,
+    },
+
+    /// Imports checkpoints stored as one yaml file each into the checkpoint store
+    MigrateCheckpoints {},
+
+    /// Prints the validators and stakes of the committee of an epoch
+    Committee {
+        /// Epoch of the committee
+        #[arg(long, value_name = "EPOCH")]
+        epoch: u64,
+    },
+
+    /// Shows how the committee changed between two epochs
+    CommitteeDiff {
+        /// Epoch of the committee to compare from
+        #[arg(long, value_name = "EPOCH")]
+        from_epoch: u64,
+
+        /// Epoch of the committee to compare to
+        #[arg(long, value_name = "EPOCH")]
+        to_epoch: u64,
+    },
+
+    /// Compares the committee derived from the stored checkpoints with the one the full
+    /// node reports on-chain, exiting non-zero if they differ
+    CheckCommittee {
+        /// Epoch of the committee
+        #[arg(long, value_name = "EPOCH")]
+        epoch: u64,
+    },
+
+    /// Probes every configured endpoint and the genesis file, exiting non-zero on failure
+    Doctor {},
+
+    /// Verifies the bundled testnet checkpoints to check the build, without a config
+    ///
+    /// Only the end-of-epoch summaries of epochs 1 to 3 are bundled, so this checks the
+    /// committee chain through them and does not extract the effects and events of a
+    /// transaction: no full checkpoint (e.g. 20873329 or 20958462) ships with the crate.
+    #[command(hide = true)]
+    SelfTest {},
+
+    /// Writes a bundle proving a transaction, verifiable offline with verify-bundle
+    Export {
+        /// Transaction hash
+        #[arg(short, long, value_name = "TID")]
+        tid: String,
+
+        /// Bundle file to write
+        #[arg(short, long, value_name = "FILE")]
+        out: PathBuf,
+    },
+
+    /// Verifies the transaction proven by an exported bundle, without network access
+    VerifyBundle {
+        /// Bundle file written by export
+        #[arg(short, long, value_name = "FILE")]
+        bundle: PathBuf,
+
+        /// Output format of the verification result
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
 }
 
-impl Config {
-    pub fn sui_rest_url(&self) -> String {
-        format!("{}/rest", self.sui_full_node_url)
+impl SCommands {
+    /// Whether the command reads from both the full node and the object store, so a
+    /// mismatch between their chains is worth checking for first
+    fn reads_the_chain(&self) -> bool {
+        matches!(
+            self,
+            SCommands::Init { .. }
+                | SCommands::Sync { .. }
+                | SCommands::Watch { .. }
+                | SCommands::Transaction { .. }
+                | SCommands::SubmitProofs { .. }
+                | SCommands::Object { .. }
+                | SCommands::DynamicField { .. }
+                | SCommands::Export { .. }
+        )
     }
+}
 
-    pub fn dwallet_full_node_url(&self) -> String {
-        format!("{}", self.dwallet_full_node_url)
-    }
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    /// Human readable text
+    Text,
+    /// A single JSON object
+    Json,
 }
 
+async fn print_verified_transaction(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    tid: &str,
+    checkpoint: Option<u64>,
+    wait: Option<u64>,
+    committee_file: Option<&Path>,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let tid = parse_transaction_digest(tid)?;
+    let checkpoint = match (checkpoint, wait) {
+        (None, Some(secs)) => Some(
+            client
+                .wait_for_transaction_checkpoint(tid, Duration::from_secs(secs))
+                .await?,
+        ),
+        _ => checkpoint,
+    };
+    let committee = committee_file.map(read_committee_file).transpose()?;
+    let verified = client
+        .get_verified_transaction(tid, checkpoint, committee.as_ref())
+        .await?;
+    let transaction = decode_transaction_data(client, resolver, &verified.data).await?;
+    print_effects_and_events(
+        client,
+        resolver,
+        &verified.effects,
+        verified.events.as_ref(),
+        Some(&verified.committee),
+        Some(&transaction),
+        format,
+    )
+    .await
+}
 
+/// Decodes the inputs and commands of verified transaction data. Pure inputs are decoded
+/// with the parameter types of the functions they are passed to and the type arguments
+/// of Move calls are resolved, both through the resolver; offline, where packages cannot
+/// be fetched, pure inputs are kept as raw BCS.
+async fn decode_transaction_data(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    data: &TransactionData,
+) -> anyhow::Result<serde_json::Value> {
+    let TransactionKind::ProgrammableTransaction(pt) = data.kind() else {
+        return Ok(json!({ "sender": data.sender(), "kind": data.kind().name() }));
+    };
 
-// The list of checkpoints at the end of each epoch
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-struct CheckpointsList {
-    // List of end of epoch checkpoints
-    checkpoints: Vec<u64>,
-}
+    let pure_layouts = if client.is_offline() {
+        vec![None; pt.inputs.len()]
+    } else {
+        resolver
+            .pure_input_layouts(pt)
+            .await
+            .context("resolving the layouts of the pure inputs")?
+    };
+    let mut inputs = vec![];
+    for (input, layout) in pt.inputs.iter().zip(pure_layouts) {
+        inputs.push(match (input, layout) {
+            (CallArg::Pure(bytes), Some(layout)) => json!({
+                "pure": SuiJsonValue::from_bcs_bytes(Some(&layout), bytes)?.to_json_value()
+            }),
+            (CallArg::Pure(bytes), None) => json!({ "pure": { "bcs": Base64::encode(bytes) } }),
+            (CallArg::Object(object), _) => json!({ "object": object_arg_json(object) }),
+        });
+    }
 
-fn read_checkpoint_list(config: &Config) -> anyhow::Result<CheckpointsList> {
-    let mut checkpoints_path = config.checkpoint_summary_dir.clone();
-    checkpoints_path.push("checkpoints.yaml");
-    // Read the resulting file and parse the yaml checkpoint list
-    let reader = fs::File::open(checkpoints_path.clone())?;
-    Ok(serde_yaml::from_reader(reader)?)
+    let mut commands = vec![];
+    for command in &pt.commands {
+        let Command::MoveCall(call) = command else {
+            commands.push(json!({ "command": command.to_string() }));
+            continue;
+        };
+        if !client.is_offline() {
+            for type_argument in &call.type_arguments {
+                resolver
+                    .type_layout(type_argument.clone())
+                    .await
+                    .with_context(|| format!("resolving type argument {}", type_argument))?;
+            }
+        }
+        commands.push(json!({
+            "move_call": {
+                "function": format!("{}::{}::{}", call.package, call.module, call.function),
+                "type_arguments": call
+                    .type_arguments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+                "arguments": call
+                    .arguments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+            }
+        }));
+    }
+
+    Ok(json!({
+        "sender": data.sender(),
+        "kind": data.kind().name(),
+        "inputs": inputs,
+        "commands": commands,
+    }))
 }
 
-fn read_checkpoint(
-    config: &Config,
-    seq: u64,
-) -> anyhow::Result<Envelope<CheckpointSummary, AuthorityQuorumSignInfo<true>>> {
-    read_checkpoint_general(config, seq, None)
+/// An object input of a programmable transaction as JSON
+fn object_arg_json(object: &ObjectArg) -> serde_json::Value {
+    match object {
+        ObjectArg::ImmOrOwnedObject((id, version, digest)) => json!({
+            "kind": "imm_or_owned",
+            "object_id": id,
+            "version": version,
+            "digest": digest,
+        }),
+        ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable,
+        } => json!({
+            "kind": "shared",
+            "object_id": id,
+            "initial_shared_version": initial_shared_version,
+            "mutable": mutable,
+        }),
+        ObjectArg::Receiving((id, version, digest)) => json!({
+            "kind": "receiving",
+            "object_id": id,
+            "version": version,
+            "digest": digest,
+        }),
+    }
 }
 
-fn read_checkpoint_general(
-    config: &Config,
-    seq: u64,
-    path: Option<&str>,
-) -> anyhow::Result<Envelope<CheckpointSummary, AuthorityQuorumSignInfo<true>>> {
-    // Read the resulting file and parse the yaml checkpoint list
-    let mut checkpoint_path = config.checkpoint_summary_dir.clone();
-    if let Some(path) = path {
-        checkpoint_path.push(path);
-    }
-    checkpoint_path.push(format!("{}.yaml", seq));
-    let mut reader = fs::File::open(checkpoint_path.clone())?;
-    let metadata = fs::metadata(&checkpoint_path)?;
-    let mut buffer = vec![0; metadata.len() as usize];
-    reader.read_exact(&mut buffer)?;
-    bcs::from_bytes(&buffer).map_err(|_| anyhow!("Unable to parse checkpoint file"))
-}
-
-fn write_checkpoint(
-    config: &Config,
-    summary: &Envelope<CheckpointSummary, AuthorityQuorumSignInfo<true>>,
-) -> anyhow::Result<()> {
-    write_checkpoint_general(config, summary, None)
+/// Reads a BCS encoded committee, e.g. written to the checkpoint directory by a sync.
+fn read_committee_file(path: &Path) -> anyhow::Result<Committee> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Unable to read committee {}", path.display()))?;
+    let committee: Committee = bcs::from_bytes(&bytes)
+        .with_context(|| format!("Unable to parse committee {}", path.display()))?;
+    Ok(committee_from_stakes(
+        committee.epoch,
+        committee.voting_rights,
+    )?)
 }
 
-fn write_checkpoint_general(
-    config: &Config,
-    summary: &Envelope<CheckpointSummary, AuthorityQuorumSignInfo<true>>,
-    path: Option<&str>,
+async fn print_effects_and_events(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    effects: &TransactionEffects,
+    events: Option<&TransactionEvents>,
+    committee: Option<&Committee>,
+    transaction: Option<&serde_json::Value>,
+    format: OutputFormat,
 ) -> anyhow::Result<()> {
-    // Write the checkpoint summary to a file
-    let mut checkpoint_path = config.checkpoint_summary_dir.clone();
-    if let Some(path) = path {
-        checkpoint_path.push(path);
+    let decoded_events = decode_events(client, resolver, events).await?;
+
+    let exec_digests = effects.execution_digests();
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Executed TID: {} Effects: {}\n - Status: {:?}\n - Gas: {:?}",
+                exec_digests.transaction,
+                exec_digests.effects,
+                effects.status(),
+                effects.gas_cost_summary()
+            );
+            if let Some(committee) = committee {
+                println!(
+                    " - Committee: epoch {}, {} validators, total stake {}",
+                    committee.epoch,
+                    committee.num_members(),
+                    committee.total_votes()
+                );
+            }
+            if let Some(transaction) = transaction {
+                println!(
+                    "Transaction:\n{}",
+                    serde_json::to_string_pretty(transaction)?
+                );
+            }
+            for (event, contents) in &decoded_events {
+                println!(
+                    "Event:\n - Package: {}\n - Module: {}\n - Sender: {}\n - Type: {}\n{}",
+                    event.package_id,
+                    event.transaction_module,
+                    event.sender,
+                    event.type_,
+                    serde_json::to_string_pretty(contents)?
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let mut output = verification_json(effects, &decoded_events, committee);
+            if let Some(transaction) = transaction {
+                output["transaction"] = transaction.clone();
+            }
+            println!("{}", serde_json::to_string(&output)?);
+        }
     }
-    checkpoint_path.push(format!("{}.yaml", summary.sequence_number));
-    let mut writer = fs::File::create(checkpoint_path.clone())?;
-    let bytes =
-        bcs::to_bytes(&summary).map_err(|_| anyhow!("Unable to serialize checkpoint summary"))?;
-    writer.write_all(&bytes)?;
+
     Ok(())
 }
 
-fn write_checkpoint_list(
-    config: &Config,
-    checkpoints_list: &CheckpointsList,
-) -> anyhow::Result<()> {
-    // Write the checkpoint list to a file
-    let mut checkpoints_path = config.checkpoint_summary_dir.clone();
-    checkpoints_path.push("checkpoints.yaml");
-    let mut writer = fs::File::create(checkpoints_path.clone())?;
-    let bytes = serde_yaml::to_vec(&checkpoints_list)?;
-    writer
-        .write_all(&bytes)
-        .map_err(|_| anyhow!("Unable to serialize checkpoint list"))
+/// Decodes the contents of each event of a transaction.
+async fn decode_events<'a>(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    events: Option<&'a TransactionEvents>,
+) -> anyhow::Result<Vec<(&'a Event, serde_json::Value)>> {
+    let mut decoded_events = vec![];
+    for event in events.into_iter().flat_map(|events| events.data.iter()) {
+        decoded_events.push((event, decode_event(client, resolver, event).await?));
+    }
+    Ok(decoded_events)
 }
 
-async fn download_checkpoint_summary(
-    config: &Config,
-    checkpoint_number: u64,
-) -> anyhow::Result<CertifiedCheckpointSummary> {
-    // Download the checkpoint from the server
-
-    let url = Url::parse(&config.object_store_url)?;
-    let (dyn_store, _store_path) = parse_url(&url).unwrap();
-    let path = Path::from(format!("{}.chk", checkpoint_number));
-    let response = dyn_store.get(&path).await?;
-    let bytes = response.bytes().await?;
-    let (_, blob) = bcs::from_bytes::<(u8, CheckpointData)>(&bytes)?;
-
-    info!("Downloaded checkpoint summary: {}", checkpoint_number);
-    Ok(blob.checkpoint_summary)
-}
-
-async fn query_last_checkpoint_of_epoch(config: &Config, epoch_id: u64) -> anyhow::Result<u64> {
-    // GraphQL query to get the last checkpoint of an epoch
-    let query = json!({
-        "query": "query ($epochID: Int) { epoch(id: $epochID) { checkpoints(last: 1) { nodes { sequenceNumber } } } }",
-        "variables": { "epochID": epoch_id }
+/// The verification result of a transaction as a single JSON object, as printed with
+/// `--format json` and served by `serve`.
+fn verification_json(
+    effects: &TransactionEffects,
+    decoded_events: &[(&Event, serde_json::Value)],
+    committee: Option<&Committee>,
+) -> serde_json::Value {
+    let exec_digests = effects.execution_digests();
+    let mut output = json!({
+        "transaction_digest": exec_digests.transaction,
+        "effects_digest": exec_digests.effects,
+        "gas_used": effects.gas_cost_summary(),
+        "status": effects.status(),
+        "object_changes": classify_object_changes(effects),
+        "events": decoded_events
+            .iter()
+            .map(|(event, contents)| json!({
+                "type": event.type_.to_string(),
+                "contents": contents,
+            }))
+            .collect::<Vec<_>>(),
     });
+    if let Some(committee) = committee {
+        output["committee"] = json!({
+            "epoch": committee.epoch,
+            "validators": committee.num_members(),
+            "total_stake": committee.total_votes(),
+        });
+    }
+    output
+}
 
-    // Submit the query by POSTing to the GraphQL endpoint
-    let client = reqwest::Client::new();
-    let resp = client
-        .post(&config.graphql_url)
-        .header("Content-Type", "application/json")
-        .body(query.to_string())
-        .send()
-        .await
-        .expect("Cannot connect to graphql")
-        .text()
+/// Decodes the contents of an event using the verified package bytecode, or keeps them
+/// as raw BCS offline, where packages cannot be fetched.
+async fn decode_event(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    event: &Event,
+) -> anyhow::Result<serde_json::Value> {
+    if client.is_offline() {
+        return Ok(json!({ "bcs": Base64::encode(&event.contents) }));
+    }
+    let type_layout = resolver
+        .type_layout(event.type_.clone().into())
         .await
-        .expect("Cannot parse response");
-
-    // Parse the JSON response to get the last checkpoint of the epoch
-    let v: Value = serde_json::from_str(resp.as_str()).expect("Incorrect JSON response");
-    let checkpoint_number = v["data"]["epoch"]["checkpoints"]["nodes"][0]["sequenceNumber"]
-        .as_u64()
-        .unwrap();
-
-    Ok(checkpoint_number)
+        .with_context(|| format!("resolving type layout of {}", event.type_))?;
+    let json_val = SuiJsonValue::from_bcs_bytes(Some(&type_layout), &event.contents)?;
+    Ok(json_val.to_json_value())
 }
 
+/// Verifies every transaction of checkpoint `seq` and prints the events of `event_type`
+/// they emitted, as an alternative to trusting the events the full node returns.
+async fn print_verified_checkpoint_events(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    seq: u64,
+    event_type: &str,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let event_type = parse_sui_struct_tag(event_type)
+        .with_context(|| format!("invalid event type {}", event_type))?;
+    // A type without type arguments matches all of its instantiations
+    let matches = |tag: &StructTag| {
+        tag.address == event_type.address
+            && tag.module == event_type.module
+            && tag.name == event_type.name
+            && (event_type.type_params.is_empty() || tag.type_params == event_type.type_params)
+    };
 
-/// Run binary search to for each end of epoch checkpoint that is missing
-/// between the latest on the list and the latest checkpoint.
-async fn sync_checkpoint_list_to_latest(config: &Config) -> anyhow::Result<()> {
-    // Get the local checkpoint list
-    let mut checkpoints_list: CheckpointsList = read_checkpoint_list(config)?;
-    let latest_in_list = checkpoints_list
-        .checkpoints
-        .last()
-        .ok_or(anyhow!("Empty checkpoint list"))?;
+    let transactions = client.verify_checkpoint(seq).await?;
+    let mut matched = vec![];
+    for (tid, _, events) in &transactions {
+        for event in events.iter().flat_map(|events| events.data.iter()) {
+            if matches(&event.type_) {
+                matched.push((tid, event, decode_event(client, resolver, event).await?));
+            }
+        }
+    }
 
-    println!("Latest in list: {}", latest_in_list);
-    // Download the latest in list checkpoint
-    let summary = download_checkpoint_summary(config, *latest_in_list)
-        .await
-        .context("Failed to download checkpoint")?;
-    let mut last_epoch = summary.epoch();
-    let mut last_checkpoint_seq = summary.sequence_number;
+    match format {
+        OutputFormat::Text => {
+            println!(
+                "Verified checkpoint {}: {} transactions, {} events of type {}",
+                seq,
+                transactions.len(),
+                matched.len(),
+                event_type
+            );
+            for (tid, event, contents) in &matched {
+                println!(
+                    "Event:\n - TID: {}\n - Sender: {}\n - Type: {}\n{}",
+                    tid,
+                    event.sender,
+                    event.type_,
+                    serde_json::to_string_pretty(contents)?
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let output = json!({
+                "checkpoint": seq,
+                "events": matched
+                    .iter()
+                    .map(|(tid, event, contents)| json!({
+                        "transaction_digest": tid,
+                        "sender": event.sender,
+                        "type": event.type_.to_string(),
+                        "contents": contents,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string(&output)?);
+        }
+    }
 
-    // Download the very latest checkpoint
-    let sui_client = SuiClientBuilder::default()
-        .build(config.sui_full_node_url.as_str())
-        .await
-        .expect("Cannot connect to full node");
+    Ok(())
+}
 
-    let latest_seq = sui_client
-        .read_api()
-        .get_latest_checkpoint_sequence_number()
-        .await
-        .unwrap();
-    let latest = download_checkpoint_summary(config, latest_seq).await?;
-    println!("Latest: {}", latest.epoch());
-    // Sequentially record all the missing end of epoch checkpoints numbers
-    while last_epoch + 1 < latest.epoch() {
-        let target_epoch = last_epoch + 1;
-        let target_last_checkpoint_number =
-            query_last_checkpoint_of_epoch(config, target_epoch).await?;
-
-        // Add to the list
-        checkpoints_list
-            .checkpoints
-            .push(target_last_checkpoint_number);
-        write_checkpoint_list(config, &checkpoints_list)?;
-
-        // Update
-        last_epoch = target_epoch;
+async fn export_bundle(client: &LightClient, tid: &str, out: &Path) -> anyhow::Result<()> {
+    let tid = parse_transaction_digest(tid)?;
+    let bundle = client.export_bundle(tid).await?;
+    fs::write(out, bundle.to_bytes()?)
+        .with_context(|| format!("Unable to write bundle {}", out.display()))?;
+    println!(
+        "Exported transaction {} in checkpoint {} with {} end of epoch checkpoints to {}",
+        tid,
+        bundle.full_checkpoint.checkpoint_summary.sequence_number,
+        bundle.end_of_epoch_checkpoints.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+async fn verify_bundle(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    path: &Path,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Unable to read bundle {}", path.display()))?;
+    let bundle = ProofBundle::from_bytes(&bytes)?;
+    let (effects, events) = bundle.verify(&client.load_genesis_committee()?)?;
+    print_effects_and_events(
+        client,
+        resolver,
+        &effects,
+        events.as_ref(),
+        None,
+        None,
+        format,
+    )
+    .await
+}
 
+/// Prints the events emitted by a dWallet transaction, decoded with the type layouts from
+/// `resolver`, or as decoded by the dWallet full node if a layout cannot be resolved.
+async fn print_dwallet_events(
+    resolver: &CachingResolver,
+    events: &[SuiEvent],
+) -> anyhow::Result<()> {
+    for event in events {
+        let contents = match resolver.type_layout(event.type_.clone().into()).await {
+            Ok(type_layout) => {
+                SuiJsonValue::from_bcs_bytes(Some(&type_layout), &event.bcs)?.to_json_value()
+            }
+            Err(e) => {
+                debug!(
+                    type_ = %event.type_,
+                    error = %e,
+                    "Unable to resolve event type, using the full node decoding"
+                );
+                event.parsed_json.clone()
+            }
+        };
         println!(
-            "Last Epoch: {} Last Checkpoint: {}",
-            target_epoch, target_last_checkpoint_number
+            "Event:\n - Package: {}\n - Module: {}\n - Sender: {}\n - Type: {}\n{}",
+            event.package_id,
+            event.transaction_module,
+            event.sender,
+            event.type_,
+            serde_json::to_string_pretty(&contents)?
         );
     }
-
     Ok(())
 }
 
+/// Parses a transaction digest given by the user, naming the expected format on failure
+fn parse_transaction_digest(tid: &str) -> anyhow::Result<TransactionDigest> {
+    TransactionDigest::from_str(tid).map_err(|e| {
+        anyhow!(
+            "invalid transaction digest '{}': expected base58-encoded 32-byte digest: {}",
+            tid,
+            e
+        )
+    })
+}
 
+/// Parses an object id given by the user, naming the expected format on failure
+fn parse_object_id(id: &str) -> anyhow::Result<ObjectID> {
+    ObjectID::from_hex_literal(id).map_err(|e| {
+        anyhow!(
+            "invalid object id '{}': expected 0x-prefixed hex of at most 32 bytes: {}",
+            id,
+            e
+        )
+    })
+}
 
-async fn check_and_sync_checkpoints(config: &Config) -> anyhow::Result<()> {
-    println!("Syncing checkpoints to latest");
-    sync_checkpoint_list_to_latest(config)
-        .await
-        .context("Failed to sync checkpoints")?;
-    println!("Synced checkpoints to latest");
-
-    // Get the local checkpoint list
-    let checkpoints_list: CheckpointsList = read_checkpoint_list(config)?;
-    println!("Checkpoints: {:?}", checkpoints_list.checkpoints);
+async fn print_verified_object(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    id: &str,
+    version: Option<u64>,
+) -> anyhow::Result<()> {
+    let oid = parse_object_id(id)?;
+    let object = match version {
+        Some(version) => {
+            client
+                .get_verified_object_at_version(oid, SequenceNumber::from_u64(version))
+                .await?
+        }
+        None => client.get_verified_object(oid).await?,
+    };
+    print_object(resolver, &object).await
+}
 
-    // Load the genesis committee
-    let mut genesis_path = config.checkpoint_summary_dir.clone();
-    genesis_path.push(&config.genesis_filename);
-    let mut genesis_committee = Genesis::load(&genesis_path)?.committee()?;
-    genesis_committee.epoch = 1; // TOOD hack to make it work
+/// Verifies the dynamic field of `parent` named by `name_json` of type `name_type` and
+/// prints it like an object.
+async fn print_verified_dynamic_field(
+    client: &LightClient,
+    resolver: &CachingResolver,
+    parent: &str,
+    name_type: &str,
+    name_json: &str,
+) -> anyhow::Result<()> {
+    let parent = parse_object_id(parent)?;
+    let name_type = parse_sui_type_tag(name_type)
+        .with_context(|| format!("invalid name type {}", name_type))?;
+    let name_value: serde_json::Value =
+        serde_json::from_str(name_json).with_context(|| format!("invalid name {}", name_json))?;
+
+    // Encode the name with the layout of its type, as the field id is derived from its BCS
+    let name_layout = resolver
+        .type_layout(name_type.clone())
+        .await
+        .with_context(|| format!("resolving type layout of {}", name_type))?;
+    let name_bcs = SuiJsonValue::new(name_value)?
+        .to_bcs_bytes(&name_layout)
+        .with_context(|| format!("encoding name {} as {}", name_json, name_type))?;
+
+    let object = client
+        .get_verified_dynamic_field(parent, &name_type, &name_bcs)
+        .await?;
+    print_object(resolver, &object).await
+}
 
-    // Retrieve highest epoch committee id that was registered on dWallet newtwork
-    let latest_registered_epoch_committee_id = retrieve_highest_epoch(config).await.unwrap_or(0);
+async fn print_object(resolver: &CachingResolver, object: &Object) -> anyhow::Result<()> {
+    let (oid, version, digest) = object.compute_object_reference();
     println!(
-        "Latest registered checkpoint id: {}",
-        latest_registered_epoch_committee_id
+        "OID: {}\n - Version: {}\n - Digest: {}\n - Owner: {}",
+        oid, version, digest, object.owner
     );
 
-    // Check the signatures of all checkpoints
-    // And download any missing ones
-    let mut prev_committee = genesis_committee;
-    // let mut prev_committee_object_ref_dwltn = genesis_committee_object_ref_dwltn;
-    for ckp_id in &checkpoints_list.checkpoints {
-        // check if there is a file with this name ckp_id.yaml in the checkpoint_summary_dir
-        let mut checkpoint_path = config.checkpoint_summary_dir.clone();
-        checkpoint_path.push(format!("{}.yaml", ckp_id));
-
-        // If file exists read the file otherwise download it from the server
-        println!("Processing checkpoint: {}", ckp_id);
-        let summary = if checkpoint_path.exists() {
-            read_checkpoint(config, *ckp_id)?
-        } else {
-            // Download the checkpoint from the server
-            println!("Downloading checkpoint: {}", ckp_id);
-            download_checkpoint_summary(config, *ckp_id)
-                .await
-                .context("Failed to download checkpoint")?
-        };
-        println!("{}", summary.auth_sig().epoch);
-        println!("{}", summary.data().epoch);
+    match &object.data {
+        Data::Move(move_object) => {
+            println!(
+                " - Type: {}\n{}",
+                move_object.type_(),
+                serde_json::to_string_pretty(&decode_move_object(resolver, move_object).await?)?
+            );
+        }
+        Data::Package(package) => {
+            println!(" - Type: package");
+            for module in package.serialized_module_map().keys() {
+                println!("   - Module: {}", module);
+            }
+        }
+    }
 
-        summary.clone().try_into_verified(&prev_committee)?;
-        println!("verified checkpoint");
+    Ok(())
+}
 
-        // Check if the checkpoint needs to be submitted to the dwallet network
-        if (latest_registered_epoch_committee_id < summary.epoch()) {
-            let mut ptb = ProgrammableTransactionBuilder::new();
+/// Decodes the contents of a Move object using the verified package bytecode.
+async fn decode_move_object(
+    resolver: &CachingResolver,
+    move_object: &MoveObject,
+) -> anyhow::Result<serde_json::Value> {
+    let object_type = move_object.type_().clone();
+    let type_layout = resolver
+        .type_layout(object_type.clone().into())
+        .await
+        .with_context(|| format!("resolving type layout of {}", object_type))?;
+    let json_val = SuiJsonValue::from_bcs_bytes(Some(&type_layout), move_object.contents())?;
+    Ok(json_val.to_json_value())
+}
 
-            let prev_committee_object_id = retieve_epoch_committee_id_by_epoch(
-                config,
-                summary.epoch().checked_sub(1).unwrap(),
-            )
-            .await
-            .unwrap();
-            let prev_committee_object_ref_dwltn =
-                get_object_ref_by_id(config, prev_committee_object_id)
-                    .await
-                    .unwrap();
+/// Runs a lightweight probe of each configured endpoint and the genesis file, printing
+/// a pass/fail table, and returns whether all of them passed.
+async fn run_doctor(client: &LightClient) -> bool {
+    let config = client.config();
+    let mut results: Vec<(&str, String, anyhow::Result<String>)> = vec![];
 
-            let registry_object_id =
-                ObjectID::from_hex_literal(&config.dwltn_registry_object_id).unwrap();
-            // retrieve highest shared version of the registry
-            let dwallet_client = SuiClientBuilder::default()
-                .build(config.dwallet_full_node_url())
-                .await
-                .unwrap();
-            let res = dwallet_client
-                .read_api()
-                .get_object_with_options(
-                    registry_object_id,
-                    SuiObjectDataOptions::full_content().with_bcs(),
-                )
-                .await
-                .unwrap();
-            let registry_initial_shared_version = match res.owner().unwrap() {
-                Owner::Shared {
-                    initial_shared_version,
-                } => initial_shared_version,
-                _ => return Err(anyhow::anyhow!("Expected a Shared owner")),
-            };
+    let probe = client.timed("Full node probe", async {
+        let read_api = client.sui_client().await?.read_api();
+        let seq = read_api.get_latest_checkpoint_sequence_number().await?;
+        Ok::<_, anyhow::Error>(format!("latest checkpoint {}", seq))
+    });
+    results.push((
+        "Full node",
+        config.sui_full_node_url.clone(),
+        probe.await.and_then(|result| result),
+    ));
+
+    let probe = client
+        .dwallet_request("dWallet full node probe", |dwallet_client| {
+            dwallet_client.read_api().get_reference_gas_price()
+        })
+        .await
+        .map(|gas_price| format!("reference gas price {}", gas_price));
+    results.push(("dWallet full node", config.dwallet_full_node_url(), probe));
 
-            let registry_arg = ptb
-                .obj(ObjectArg::SharedObject {
-                    id: registry_object_id,
-                    initial_shared_version: registry_initial_shared_version,
-                    mutable: true,
-                })
-                .unwrap();
-            let prev_committee_arg = ptb
-                .obj(ObjectArg::ImmOrOwnedObject(prev_committee_object_ref_dwltn))
-                .unwrap();
-            let new_checkpoint_summary_arg = ptb.pure(bcs::to_bytes(&summary).unwrap()).unwrap();
-
-            let call = ProgrammableMoveCall {
-                package: ObjectID::from_hex_literal(
-                    "0x0000000000000000000000000000000000000000000000000000000000000003",
-                )
-                .unwrap(),
-                module: Identifier::new("sui_state_proof").unwrap(),
-                function: Identifier::new("submit_new_state_committee").unwrap(),
-                type_arguments: vec![],
-                arguments: vec![registry_arg, prev_committee_arg, new_checkpoint_summary_arg],
-            };
+    let (name, url) = match config.checkpoint_source {
+        CheckpointSource::ObjectStore => ("Object store", config.object_store_url.clone()),
+        CheckpointSource::FullNode => ("Full node REST", config.sui_rest_url()),
+    };
+    let probe = client.timed(&format!("{} probe", name), async {
+        let summary = client.download_checkpoint_summary(0).await?;
+        Ok::<_, anyhow::Error>(format!("checkpoint 0, digest {}", summary.digest()))
+    });
+    results.push((name, url, probe.await.and_then(|result| result)));
+
+    // The binary search epoch lookup goes through the full node and object store only
+    if config.epoch_lookup == EpochLookup::Graphql {
+        let probe = client.timed("GraphQL probe", async {
+            let seq = client.query_last_checkpoint_of_epoch(0).await?;
+            Ok::<_, anyhow::Error>(format!("epoch 0 ends at checkpoint {}", seq))
+        });
+        results.push((
+            "GraphQL",
+            config.graphql_url.clone(),
+            probe.await.and_then(|result| result),
+        ));
+    }
 
-            let dwallet_client = SuiClientBuilder::default()
-                .build(config.dwallet_full_node_url())
-                .await
-                .unwrap();
+    results.push((
+        "Chain",
+        "full node and object store".to_string(),
+        client.check_chain_identifier().await,
+    ));
+
+    results.push((
+        "Genesis",
+        config
+            .checkpoint_summary_dir
+            .join(&config.genesis_filename)
+            .display()
+            .to_string(),
+        client
+            .load_genesis_committee()
+            .map(|committee| format!("{} validators", committee.num_members())),
+    ));
+
+    // The protocol version of the newest verified epoch, the frontier later checkpoints
+    // are decoded against
+    results.push((
+        "Protocol version",
+        config
+            .checkpoint_summary_dir
+            .join("committees")
+            .display()
+            .to_string(),
+        client
+            .read_protocol_versions()
+            .map(|versions| match versions.last_key_value() {
+                Some((epoch, version)) => format!("epoch {} runs version {}", epoch, version),
+                None => "none recorded yet, run sync".to_string(),
+            }),
+    ));
+
+    let mut all_passed = true;
+    for (name, target, result) in &results {
+        let (status, detail) = match result {
+            Ok(detail) => ("PASS", detail.clone()),
+            Err(e) => {
+                all_passed = false;
+                ("FAIL", format!("{:#}", e))
+            }
+        };
+        println!("{:<18} {:<4} {} ({})", name, status, detail, target);
+    }
+    all_passed
+}
 
-            ptb.command(Command::MoveCall(Box::new(call)));
+/// The testnet end-of-epoch checkpoints of epochs 1 to 3 from `example_config`, built into
+/// the binary so the self test needs no files or network.
+const SELF_TEST_CHECKPOINTS: [(u64, &[u8]); 3] = [
+    (90645, include_bytes!("../../example_config/90645.yaml")),
+    (176295, include_bytes!("../../example_config/176295.yaml")),
+    (256378, include_bytes!("../../example_config/256378.yaml")),
+];
 
-            let builder = ptb.finish();
+/// Decodes and verifies the bundled checkpoints, printing a pass/fail table like the
+/// doctor, and returns whether all checks passed.
+fn run_self_test() -> bool {
+    let mut results: Vec<(&str, anyhow::Result<String>)> = vec![];
 
-            let gas_budget = 1000000000;
-            let gas_price = dwallet_client
-                .read_api()
-                .get_reference_gas_price()
-                .await
-                .unwrap();
+    let summaries: anyhow::Result<Vec<CertifiedCheckpointSummary>> = SELF_TEST_CHECKPOINTS
+        .iter()
+        .map(|(seq, bytes)| {
+            bcs::from_bytes(bytes).with_context(|| format!("Unable to decode checkpoint {}", seq))
+        })
+        .collect();
+    let summaries = match summaries {
+        Ok(summaries) => summaries,
+        Err(e) => {
+            println!("{:<20} FAIL {:#}", "Decode checkpoints", e);
+            return false;
+        }
+    };
+    results.push((
+        "Decode checkpoints",
+        Ok(format!(
+            "{} checkpoints of epochs {} to {}",
+            summaries.len(),
+            summaries[0].epoch(),
+            summaries[summaries.len() - 1].epoch()
+        )),
+    ));
+
+    // Each checkpoint is signed by the committee the previous one hands over to
+    let chain = (|| {
+        let mut committee = next_committee_from_summary(&summaries[0])?;
+        for summary in &summaries[1..] {
+            committee = verify_end_of_epoch_summary(summary, &committee)?;
+        }
+        Ok::<_, anyhow::Error>(format!(
+            "verified up to the committee of epoch {}",
+            committee.epoch
+        ))
+    })();
+    results.push(("Committee chain", chain));
+
+    // A committee of another epoch must not verify a checkpoint
+    let rejected = (|| {
+        let committee = next_committee_from_summary(&summaries[0])?;
+        match verify_end_of_epoch_summary(&summaries[2], &committee) {
+            Ok(_) => Err(anyhow!(
+                "checkpoint {} verified against the committee of epoch {}",
+                summaries[2].sequence_number,
+                committee.epoch
+            )),
+            Err(e) => Ok(format!("rejected: {}", e)),
+        }
+    })();
+    results.push(("Wrong committee", rejected));
+
+    let mut all_passed = true;
+    for (name, result) in &results {
+        let (status, detail) = match result {
+            Ok(detail) => ("PASS", detail.clone()),
+            Err(e) => {
+                all_passed = false;
+                ("FAIL", format!("{:#}", e))
+            }
+        };
+        println!("{:<20} {:<4} {}", name, status, detail);
+    }
+    all_passed
+}
 
-            let keystore =
-                FileBasedKeystore::new(&sui_config_dir().unwrap().join(SUI_KEYSTORE_FILENAME))
-                    .unwrap();
+/// Submits the proofs of transactions `tids` and prints the outcome of each, returning
+/// whether all of them were submitted.
+async fn submit_transaction_proofs(
+    client: &LightClient,
+    tids: &[String],
+    dry_run: bool,
+) -> anyhow::Result<bool> {
+    let tids = tids
+        .iter()
+        .map(|tid| parse_transaction_digest(tid))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let submission = client.submit_transaction_proofs(&tids, dry_run).await?;
 
-            let sender = *keystore.addresses_with_alias().first().unwrap().0;
-            println!("sender: {}", sender);
+    if let Some(digest) = submission.digest {
+        println!("dWallet transaction: {}", digest);
+    }
+    let verb = if dry_run { "Would submit" } else { "Submitted" };
+    for tid in &submission.submitted {
+        println!(" - {}: {}", tid, verb);
+    }
+    for (tid, e) in &submission.failed {
+        println!(" - {}: Failed: {:#}", tid, e);
+    }
+    Ok(submission.failed.is_empty())
+}
 
-            let coins = dwallet_client
-                .coin_read_api()
-                .get_coins(sender, None, None, None)
-                .await
-                .unwrap();
-            let coin_gas = coins
-                .data
-                .into_iter()
-                .max_by_key(|coin| coin.balance)
-                .unwrap();
-
-            let tx_data = TransactionData::new_programmable(
-                sender,
-                vec![coin_gas.object_ref()],
-                builder,
-                gas_budget,
-                gas_price,
-            );
+/// Prints the committee of `epoch` as derived from the stored end-of-epoch checkpoints,
+/// with the network address of the validators known from genesis.
+fn print_committee(client: &LightClient, epoch: u64) -> anyhow::Result<()> {
+    let committee = client.committee_for_epoch(epoch)?;
+    let addresses = client.genesis_validator_addresses()?;
 
-            // 4) sign transaction
-            let signature = keystore
-                .sign_secure(&sender, &tx_data, Intent::sui_transaction())
-                .unwrap();
-
-            // 5) execute the transaction
-            println!("Executing the transaction...");
-            let transaction_response = dwallet_client
-                .quorum_driver_api()
-                .execute_transaction_block(
-                    Transaction::from_data(tx_data, vec![signature]),
-                    SuiTransactionBlockResponseOptions::full_content(),
-                    Some(ExecuteTransactionRequestType::WaitForLocalExecution),
-                )
-                .await
-                .unwrap();
-
-            let object_changes = transaction_response.object_changes.unwrap();
-
-            // println!("object changes: {}", object_changes);
-            let committee_object_change = object_changes
-                .iter()
-                .filter(|object| match object {
-                    ObjectChange::Created {
-                        sender: _,
-                        owner: _,
-                        object_type: object_type,
-                        object_id: _,
-                        version: _,
-                        digest: _,
-                    } => object_type.to_string().contains("EpochCommittee"),
-                    _ => false,
-                })
-                .next()
-                .unwrap();
-
-            // sleep 3 secs
-            sleep(std::time::Duration::from_secs(5));
-        }
-
-        // Write the checkpoint summary to a file
-        write_checkpoint(config, &summary)?;
-
-        // Print the id of the checkpoint and the epoch number
+    println!(
+        "Committee of epoch {}: {} validators",
+        committee.epoch,
+        committee.num_members()
+    );
+    for (name, stake) in committee.members() {
+        let address = addresses.get(name).map_or("unknown", String::as_str);
         println!(
-            "Epoch: {} Checkpoint ID: {}",
-            summary.epoch(),
-            summary.digest()
+            " - {}\n   Address: {}\n   Stake: {}",
+            Hex::encode(name),
+            address,
+            stake
         );
+    }
+    println!(
+        "Total stake: {}\nQuorum threshold: {}",
+        committee.total_votes(),
+        committee.quorum_threshold()
+    );
 
-        // Extract the new committee information
-        if let Some(EndOfEpochData {
-            next_epoch_committee,
-            ..
-        }) = &summary.end_of_epoch_data
-        {
-            let next_committee = next_epoch_committee.iter().cloned().collect();
-            prev_committee = Committee::new(summary.epoch().saturating_add(1), next_committee);
-        } else {
-            return Err(anyhow!(
-                "Expected all checkpoints to be end-of-epoch checkpoints"
-            ));
-        }
+    Ok(())
+}
+
+fn print_committee_diff(
+    client: &LightClient,
+    from_epoch: u64,
+    to_epoch: u64,
+) -> anyhow::Result<()> {
+    let from = client.committee_for_epoch(from_epoch)?;
+    let to = client.committee_for_epoch(to_epoch)?;
+
+    println!(
+        "Committee diff from epoch {} to epoch {}",
+        from_epoch, to_epoch
+    );
+    for change in diff_committees(&from, &to) {
+        println!(" - {}", change);
     }
 
     Ok(())
 }
 
+/// Compares the committee of `epoch` derived from the stored end-of-epoch checkpoints with
+/// the one the full node reports, returning whether they match.
+async fn check_onchain_committee(client: &LightClient, epoch: u64) -> anyhow::Result<bool> {
+    let derived = client.committee_for_epoch(epoch)?;
+    let onchain = client.fetch_onchain_committee(epoch).await?;
 
+    let changes = diff_committees(&derived, &onchain);
+    if changes.is_empty() {
+        println!(
+            "Committee of epoch {} matches the full node ({} validators)",
+            epoch,
+            derived.num_members()
+        );
+        return Ok(true);
+    }
+    println!(
+        "Committee of epoch {} differs from the full node, going from the derived committee \
+         to the on-chain one:",
+        epoch
+    );
+    for change in changes {
+        println!(" - {}", change);
+    }
+    Ok(false)
+}
 
-async fn get_verified_object(config: &Config, id: ObjectID) -> anyhow::Result<Object> {
-    let sui_client: Arc<sui_sdk::SuiClient> = Arc::new(
-        SuiClientBuilder::default()
-            .build(config.sui_full_node_url.as_str())
-            .await
-            .unwrap(),
+/// Logs the endpoints resolved from the config, also printed with `--verbose`, without
+/// contacting any of them.
+fn print_endpoints(client: &LightClient, verbose: bool) {
+    let config = client.config();
+    let endpoints = [
+        ("Full node", config.sui_full_node_url.clone()),
+        ("REST", config.sui_rest_url()),
+        ("Object store", config.object_store_url.clone()),
+        ("GraphQL", config.graphql_url.clone()),
+        ("dWallet full node", config.dwallet_full_node_url()),
+    ];
+    info!(
+        sui_full_node_url = %endpoints[0].1,
+        sui_rest_url = %endpoints[1].1,
+        object_store_url = %endpoints[2].1,
+        graphql_url = %endpoints[3].1,
+        dwallet_full_node_url = %endpoints[4].1,
+        "Using endpoints"
     );
+    if verbose {
+        for (name, url) in &endpoints {
+            println!("{:<18} {}", name, url);
+        }
+    }
+}
 
-    println!("Getting object: {}", id);
+/// Warns if the full node and the object store serve different chains, which makes every
+/// verification fail, for the commands that read from both.
+async fn print_chain_check(client: &LightClient, verbose: bool) {
+    if client.is_offline() {
+        return;
+    }
 
-    let read_api = sui_client.read_api();
-    let object_json = read_api
-        .get_object_with_options(id, SuiObjectDataOptions::bcs_lossless())
-        .await
-        .expect("Cannot get object");
-    let object = object_json
-        .into_object()
-        .expect("Cannot make into object data");
-    let object: Object = object.try_into().expect("Cannot reconstruct object");
+    match client.check_chain_identifier().await {
+        Ok(chain) => {
+            info!(%chain, "Full node and object store serve the same chain");
+            if verbose {
+                println!("{:<18} {}", "Chain", chain);
+            }
+        }
+        Err(e) => match e.downcast_ref::<LightClientError>() {
+            Some(mismatch @ LightClientError::ChainMismatch { .. }) => {
+                warn!("{}, check sui_full_node_url and object_store_url", mismatch);
+                if verbose {
+                    println!("{:<18} MISMATCH: {}", "Chain", mismatch);
+                }
+            }
+            _ => {
+                debug!("Unable to check the chain identifier: {:#}", e);
+                if verbose {
+                    println!("{:<18} unknown: {:#}", "Chain", e);
+                }
+            }
+        },
+    }
+}
 
-    Ok(object)
+/// The config fields set by a command, written back to the config file by `main`
+struct ConfigUpdate {
+    dwltn_config_object_id: String,
+    dwltn_registry_object_id: String,
+    dwltn_registry_initial_shared_version: Option<u64>,
 }
 
+impl ConfigUpdate {
+    fn apply(self, config: &mut Config) {
+        config.dwltn_config_object_id = self.dwltn_config_object_id;
+        config.dwltn_registry_object_id = self.dwltn_registry_object_id;
+        config.dwltn_registry_initial_shared_version = self.dwltn_registry_initial_shared_version;
+    }
+}
 
-async fn retrieve_highest_epoch(config: &Config) -> anyhow::Result<u64> {
-    let client = SuiClientBuilder::default()
-        .build(config.dwallet_full_node_url.clone())
-        .await
-        .unwrap();
+/// Syncs every `interval` until Ctrl-C, logging the outcome of each cycle. A failed sync
+/// is retried after a jittered exponential backoff instead, capped at the interval, so a
+/// flaky network or dWallet node does not stop the daemon.
+async fn run_watch(client: &Arc<LightClient>, interval: Duration) {
+    // The first Ctrl-C lets the current sync finish its checkpoint, a second one aborts
+    let stop = Arc::new(tokio::sync::Notify::new());
+    let interrupted_client = client.clone();
+    let interrupted = stop.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("Stopping after the current sync, Ctrl-C again to abort");
+            interrupted_client.request_stop();
+            interrupted.notify_one();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    });
 
-    let query = EventFilter::MoveModule {
-        package: ObjectID::from_hex_literal(
-            &"0x0000000000000000000000000000000000000000000000000000000000000003",
-        )
-        .unwrap(),
-        module: Identifier::from_str(&"sui_state_proof").unwrap(),
+    let mut backoff = ExponentialBackoff {
+        initial_interval: Duration::from_secs(5).min(interval),
+        max_interval: interval,
+        max_elapsed_time: None,
+        ..Default::default()
     };
-
-    let res = client
-        .event_api()
-        .query_events(query.clone(), Option::None, Option::None, true)
-        .await
-        .unwrap();
-    let max = res
-        .data
-        .iter()
-        .filter(|event| event.parsed_json.get("epoch").is_some())
-        .filter(|event| event.parsed_json.get("registry_id").unwrap().as_str().unwrap() == config.dwltn_registry_object_id)
-        .map(|event| {
-            u64::from_str(event.parsed_json.get("epoch").unwrap().as_str().unwrap()).unwrap()
-        })
-        .max()
-        .unwrap();
-    return anyhow::Ok(max);
+    let options = SyncOptions {
+        dry_run: false,
+        show_progress: false,
+        continue_on_error: false,
+    };
+    for cycle in 1.. {
+        let started = Instant::now();
+        let res = client.check_and_sync_checkpoints(options).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        let wait = match res {
+            Ok(()) => {
+                let last_processed = client
+                    .read_sync_state()
+                    .ok()
+                    .and_then(|state| state.last_processed_checkpoint);
+                info!(cycle, elapsed_ms, ?last_processed, "Sync cycle completed");
+                backoff.reset();
+                interval
+            }
+            Err(e) => {
+                let wait = backoff.next_backoff().unwrap_or(interval);
+                warn!(
+                    cycle,
+                    elapsed_ms,
+                    retry_in_secs = wait.as_secs(),
+                    "Sync cycle failed: {:#}",
+                    e
+                );
+                wait
+            }
+        };
+        if client.stop_requested() {
+            break;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = stop.notified() => break,
+        }
+    }
+    info!("Watch stopped");
 }
 
-async fn retieve_epoch_committee_id_by_epoch(
+/// Registers the committee after checkpoint `ckp_id`, or the genesis committee for 0, on
+/// the dWallet network, returning the ids of the objects created for the config.
+/// `verified` is the summary of `ckp_id` if already verified against the committee chain.
+async fn run_init(
+    light_client: &LightClient,
+    resolver: &CachingResolver,
     config: &Config,
-    target_epoch: u64,
-) -> anyhow::Result<ObjectID> {
-    let client = SuiClientBuilder::default()
-        .build(config.dwallet_full_node_url.clone())
-        .await
-        .unwrap();
+    ckp_id: u64,
+    verified: Option<CertifiedCheckpointSummary>,
+) -> anyhow::Result<ConfigUpdate> {
+    // create a PTB with init module
+    let mut ptb = ProgrammableTransactionBuilder::new();
+
+    let genesis_committee: Committee;
+    let genesis_epoch;
+
+    if ckp_id == 0 {
+        // The registry stores the committee handed over at the end of `genesis_epoch`.
+        // Genesis has no such checkpoint, so the genesis validators are registered as
+        // the committee of the epoch after `genesis_committee_epoch`. This only holds
+        // if the validator set did not change at that epoch boundary; prefer
+        // initializing from an end-of-epoch ckp_id.
+        let committee = light_client.load_genesis_committee()?;
+        genesis_epoch = committee.epoch;
+        genesis_committee = Committee::new(
+            committee.epoch + 1,
+            committee.voting_rights.into_iter().collect(),
+        );
+    } else {
+        let summary = match verified {
+            Some(summary) => summary,
+            None => {
+                let summary = light_client
+                    .download_checkpoint_summary(ckp_id)
+                    .await
+                    .with_context(|| format!("Unable to download checkpoint {}", ckp_id))?;
+                // Verify the checkpoint if the stored committee chain reaches its epoch,
+                // otherwise the committee it hands over to is trusted as is, in place of
+                // genesis
+                match light_client.committee_for_epoch(summary.epoch()) {
+                    Ok(committee) => {
+                        verify_end_of_epoch_summary(&summary, &committee)?;
+                        info!(
+                            checkpoint = ckp_id,
+                            "Verified the checkpoint against the stored committee chain"
+                        );
+                    }
+                    Err(e) => warn!(
+                        checkpoint = ckp_id,
+                        error = %e,
+                        "Unable to verify the checkpoint, trusting it instead of genesis"
+                    ),
+                }
+                summary
+            }
+        };
+        genesis_committee = next_committee_from_summary(&summary)?;
+        genesis_epoch = summary.epoch();
+        info!(epoch = summary.epoch() + 1, "Initializing with committee");
+    }
 
-    let query = EventFilter::MoveModule {
-        package: ObjectID::from_hex_literal(
-            &"0x0000000000000000000000000000000000000000000000000000000000000003",
-        )
-        .unwrap(),
-        module: Identifier::from_str(&"sui_state_proof").unwrap(),
+    let init_committee_arg = ptb
+        .pure(bcs::to_bytes(&genesis_committee)?)
+        .context("Passing the initial committee")?;
+    let package_address =
+        AccountAddress::from_hex_literal(&config.sui_deployed_state_proof_package)
+            .context("Invalid sui_deployed_state_proof_package")?;
+    let package_id_arg = ptb
+        .pure(bcs::to_bytes(&ObjectID::from(package_address))?)
+        .context("Passing the state proof package id")?;
+
+    let init_tag = StructTag {
+        address: package_address,
+        module: Identifier::new("dwallet_cap").expect("can't create identifier"),
+        name: Identifier::new("DWalletNetworkInitCapRequest").expect("can't create identifier"),
+        type_params: vec![],
     };
+    debug!(%init_tag, "Init event type");
 
-    let mut has_next = true;
-    let mut cursor = Option::None;
-    while (has_next) {
-        let res = client
-            .event_api()
-            .query_events(query.clone(), cursor, Option::None, true)
-            .await
-            .unwrap();
+    let init_type_layout = resolver
+        .type_layout(TypeTag::Struct(Box::new(init_tag)))
+        .await
+        .context("Resolving the layout of DWalletNetworkInitCapRequest")?;
 
-        let filtered: Option<&SuiEvent> = res
-            .data
-            .iter()
-            .filter(|event| event.parsed_json.get("epoch").is_some())
-            .filter(|event| {
-                u64::from_str(event.parsed_json.get("epoch").unwrap().as_str().unwrap()).unwrap()
-                    == target_epoch
-            })
-            .next();
-        if filtered.is_some() {
-            return Ok(ObjectID::from_hex_literal(
-                filtered
-                    .unwrap()
-                    .parsed_json
-                    .get("epoch_committee_id")
-                    .unwrap()
-                    .as_str()
-                    .unwrap(),
-            )
-            .unwrap());
-        }
+    let init_event_type_layout_arg = ptb
+        .pure(bcs::to_bytes(&init_type_layout)?)
+        .context("Passing the init event layout")?;
 
-        cursor = res.next_cursor;
-        has_next = res.has_next_page;
-    }
+    let approve_tag = StructTag {
+        address: package_address,
+        module: Identifier::new("dwallet_cap").expect("can't create identifier"),
+        name: Identifier::new("DWalletNetworkApproveRequest").expect("can't create identifier"),
+        type_params: vec![],
+    };
 
-    return Err(anyhow::Error::msg("Epoch not found"));
-}
+    let approve_type_layout = resolver
+        .type_layout(TypeTag::Struct(Box::new(approve_tag)))
+        .await
+        .context("Resolving the layout of DWalletNetworkApproveRequest")?;
+    let approve_event_type_layout_arg = ptb
+        .pure(bcs::to_bytes(&approve_type_layout)?)
+        .context("Passing the approve event layout")?;
+
+    let epoch_id_committee_arg = ptb
+        .pure(genesis_epoch)
+        .context("Passing the initial epoch")?;
+
+    let call = ProgrammableMoveCall {
+        package: config.dwltn_state_proof_package_id,
+        module: Identifier::new("sui_state_proof").expect("can't create identifier"),
+        function: Identifier::new("init_module").expect("can't create identifier"),
+        type_arguments: vec![],
+        arguments: vec![
+            init_committee_arg,
+            package_id_arg,
+            init_event_type_layout_arg,
+            approve_event_type_layout_arg,
+            epoch_id_committee_arg,
+        ],
+    };
 
+    ptb.command(Command::MoveCall(Box::new(call)));
 
-async fn get_object_ref_by_id(config: &Config, object_id: ObjectID) -> anyhow::Result<ObjectRef> {
-    let dwallet_client = SuiClientBuilder::default()
-        .build(config.dwallet_full_node_url())
-        .await
-        .unwrap();
-    let res = dwallet_client
-        .read_api()
-        .get_object_with_options(object_id, SuiObjectDataOptions::full_content().with_bcs())
+    // Paid, signed and executed like the sync submissions, through the dWallet retry budget
+    // and circuit breaker
+    let transaction_response = light_client
+        .execute_dwallet_transaction(ptb.finish(), false)
         .await
-        .unwrap();
-    let object_ref = res.data.unwrap().object_ref();
-    Ok(object_ref)
-}
+        .context("Executing the init transaction")?
+        .context("Init transaction was not executed")?;
+    if let Some(SuiExecutionStatus::Failure { error }) = transaction_response
+        .effects
+        .as_ref()
+        .map(|effects| effects.status())
+    {
+        anyhow::bail!(
+            "Init transaction {} failed: {}",
+            transaction_response.digest,
+            error
+        );
+    }
+
+    let object_changes = transaction_response
+        .object_changes
+        .as_deref()
+        .with_context(|| {
+            format!(
+                "No object changes in the response of init transaction {}",
+                transaction_response.digest
+            )
+        })?;
+    println!("Transaction executed {}", object_changes.len());
+    for object in object_changes {
+        println!("{}", object);
+    }
 
+    // Show the events emitted by the init, e.g. DWalletNetworkInitCapRequest
+    let events = transaction_response
+        .events
+        .as_ref()
+        .map(|events| events.data.as_slice())
+        .unwrap_or_default();
+    print_dwallet_events(resolver, events).await?;
+
+    let created = |type_substring: &str| {
+        find_created_object(object_changes, type_substring)
+            .with_context(|| format!("Init transaction {}", transaction_response.digest))
+    };
+    let registry_object_ref = created("Registry")?;
+    created("EpochCommittee")?;
+    let config_object_ref = created("StateProofConfig")?;
+
+    // The registry is shared by the transaction creating it, at its created version
+    let registry_initial_shared_version = Some(registry_object_ref.1.value());
+    Ok(ConfigUpdate {
+        dwltn_config_object_id: config_object_ref.0.to_string(),
+        dwltn_registry_object_id: registry_object_ref.0.to_string(),
+        dwltn_registry_initial_shared_version: registry_initial_shared_version,
+    })
+}
 
 #[tokio::main]
 pub async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
     // Command line arguments and config loading
     let args = Args::parse();
 
+    // The self test checks the build alone, before any config is needed
+    if let Some(SCommands::SelfTest {}) = args.command {
+        std::process::exit(if run_self_test() { 0 } else { 1 });
+    }
+
     let path = args
         .config
         .unwrap_or_else(|| panic!("Need a config file path"));
-    let reader = fs::File::open(path.clone())
-        .unwrap_or_else(|_| panic!("Unable to load config from {}", path.display()));
-    let mut config: Config = serde_yaml::from_reader(reader).unwrap();
-
-    println!("Config: {:?}", config);
-
-    // Print config parameters
-    println!(
-        "Checkpoint Dir: {}",
-        config.checkpoint_summary_dir.display()
+    let mut config = Config::load(&path).unwrap_or_else(|e| panic!("{:#}", e));
+    config
+        .validate()
+        .unwrap_or_else(|e| panic!("Invalid config in {}: {}", path.display(), e));
+
+    debug!(?config, "Loaded config");
+    info!(
+        checkpoint_dir = %config.checkpoint_summary_dir.display(),
+        "Using checkpoint directory"
     );
 
-    let sui_client: Client = Client::new(config.sui_rest_url());
-    let remote_package_store = RemotePackageStore::new(config.clone());
-    let resolver = Resolver::new(remote_package_store);
-
-    let dwallet_client = SuiClientBuilder::default()
-        .build(config.dwallet_full_node_url())
-        .await
-        .unwrap();
+    let mut client_config = config.clone();
+    client_config.offline |= args.offline;
+    let light_client = Arc::new(
+        LightClient::new(client_config)
+            .unwrap_or_else(|e| panic!("Unable to create light client: {:?}", e)),
+    );
+    let _metrics_exporter = light_client.start_metrics_exporter();
+    print_endpoints(&light_client, args.verbose);
+    if args
+        .command
+        .as_ref()
+        .is_some_and(SCommands::reads_the_chain)
+    {
+        print_chain_check(&light_client, args.verbose).await;
+    }
+    let remote_package_store = RemotePackageStore::new(light_client.clone());
+    let resolver = CachingResolver::new(remote_package_store);
 
+    // Only init changes the config, the other commands leave the file untouched
+    let mut config_update = None;
     match args.command {
-        Some(SCommands::Init { ckp_id }) => {
-            // create a PTB with init module
-            let mut ptb = ProgrammableTransactionBuilder::new();
-
-            let mut genesis_committee: Committee;
-            let mut genesis_epoch;
-
-            if ckp_id == 0 {
-                // Load the genesis committee
-                let mut genesis_path = config.checkpoint_summary_dir.clone();
-                genesis_path.push(&config.genesis_filename);
-                genesis_committee = Genesis::load(&genesis_path).unwrap().committee().unwrap();
-                genesis_committee.epoch = 1; // TOOD hack to make it work
-                genesis_epoch = 0;
-            } else {
-                let summary = download_checkpoint_summary(&config, ckp_id).await.unwrap();
-                genesis_committee = Committee::new(
-                    summary.epoch() + 1,
-                    summary
-                        .end_of_epoch_data
-                        .as_ref()
-                        .unwrap()
-                        .next_epoch_committee
-                        .iter()
-                        .cloned()
-                        .collect(),
-                );
-                genesis_epoch = summary.epoch();
-                println!("Epoch: {}", summary.epoch() + 1);
-            }
-
-            let init_committee_arg = ptb
-                .pure(bcs::to_bytes(&genesis_committee).unwrap())
-                .unwrap();
-            let package_id_arg = ptb
-                .pure(
-                    bcs::to_bytes(
-                        &ObjectID::from_hex_literal(&config.sui_deployed_state_proof_package)
-                            .unwrap(),
-                    )
-                    .unwrap(),
-                )
-                .unwrap();
-
-            let init_tag = StructTag {
-                address: AccountAddress::from_hex_literal(&config.sui_deployed_state_proof_package)
-                    .unwrap(),
-                module: Identifier::new("dwallet_cap").expect("can't create identifier"),
-                name: Identifier::new("DWalletNetworkInitCapRequest")
-                    .expect("can't create identifier"),
-                type_params: vec![],
-            };
-            println!("Init Tag: {}", init_tag);
-
-            let init_type_layout = resolver
-                .type_layout(TypeTag::Struct(Box::new(init_tag)))
-                .await
-                .unwrap();
-
-            let init_event_type_layout_arg =
-                ptb.pure(bcs::to_bytes(&init_type_layout).unwrap()).unwrap();
-
-            let approve_tag = StructTag {
-                address: AccountAddress::from_hex_literal(&config.sui_deployed_state_proof_package)
-                    .unwrap(),
-                module: Identifier::new("dwallet_cap").expect("can't create identifier"),
-                name: Identifier::new("DWalletNetworkApproveRequest")
-                    .expect("can't create identifier"),
-                type_params: vec![],
+        Some(SCommands::Init {
+            ckp_id,
+            since_epoch,
+        }) => {
+            let (ckp_id, verified) = match since_epoch {
+                Some(epoch) => {
+                    let summary = light_client
+                        .verified_end_of_epoch_summary(epoch)
+                        .await
+                        .unwrap_or_else(|e| {
+                            panic!("Unable to verify the end of epoch {}: {:?}", epoch, e)
+                        });
+                    (summary.sequence_number, Some(summary))
+                }
+                None => (ckp_id.expect("required without --since-epoch"), None),
             };
-
-            let approve_type_layout = resolver
-                .type_layout(TypeTag::Struct(Box::new(approve_tag)))
-                .await
-                .unwrap();
-            let approve_event_type_layout_arg = ptb
-                .pure(bcs::to_bytes(&approve_type_layout).unwrap())
-                .unwrap();
-
-            let epoch_id_committee_arg = ptb.pure(genesis_epoch).unwrap();
-
-            let call = ProgrammableMoveCall {
-                package: ObjectID::from_hex_literal(
-                    "0x0000000000000000000000000000000000000000000000000000000000000003",
-                )
-                .unwrap(),
-                module: Identifier::new("sui_state_proof").expect("can't create identifier"),
-                function: Identifier::new("init_module").expect("can't create identifier"),
-                type_arguments: vec![],
-                arguments: vec![
-                    init_committee_arg,
-                    package_id_arg,
-                    init_event_type_layout_arg,
-                    approve_event_type_layout_arg,
-                    epoch_id_committee_arg,
-                ],
+            match run_init(&light_client, &resolver, &config, ckp_id, verified).await {
+                Ok(update) => config_update = Some(update),
+                Err(e) => {
+                    println!("Error: {:?}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(SCommands::Sync {
+            dry_run,
+            no_progress,
+            continue_on_error,
+        }) => {
+            let options = SyncOptions {
+                dry_run,
+                show_progress: !no_progress,
+                continue_on_error,
             };
-
-            ptb.command(Command::MoveCall(Box::new(call)));
-
-            let builder = ptb.finish();
-
-            let gas_budget = 1000000000;
-            let gas_price = dwallet_client
-                .read_api()
-                .get_reference_gas_price()
+            // The first Ctrl-C lets the sync finish the checkpoint it is processing, a
+            // second one aborts
+            let interrupted_client = light_client.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    println!("Stopping after the current checkpoint, Ctrl-C again to abort");
+                    interrupted_client.request_stop();
+                    if tokio::signal::ctrl_c().await.is_ok() {
+                        std::process::exit(130);
+                    }
+                }
+            });
+            let res = light_client
+                .check_and_sync_checkpoints(options)
                 .await
-                .unwrap();
-
-            let keystore =
-                FileBasedKeystore::new(&sui_config_dir().unwrap().join(SUI_KEYSTORE_FILENAME))
-                    .unwrap();
+                .context("check and sync error");
+            if res.is_ok() && light_client.stop_requested() {
+                println!("Sync stopped, run sync again to resume");
+            }
 
-            let sender = *keystore.addresses_with_alias().first().unwrap().0;
-            println!("Address: {}", sender);
+            if let Err(e) = &res {
+                // Retrying is pointless while the dWallet network is down, report it alone
+                if let Some(unavailable @ LightClientError::DwalletUnavailable { .. }) =
+                    e.downcast_ref::<LightClientError>()
+                {
+                    println!("Error: {}", unavailable);
+                    std::process::exit(1);
+                }
+                println!("Error: {:?}", res);
+                std::process::exit(1);
+            }
+        }
+        Some(SCommands::Watch { interval_secs }) => {
+            run_watch(&light_client, Duration::from_secs(interval_secs)).await;
+        }
+        Some(SCommands::Transaction {
+            tid,
+            format,
+            checkpoint,
+            wait,
+            committee_file,
+        }) => {
+            let res = print_verified_transaction(
+                &light_client,
+                &resolver,
+                &tid,
+                checkpoint,
+                wait,
+                committee_file.as_deref(),
+                format,
+            )
+            .await
+            .context("transaction verification error");
 
-            let coins = dwallet_client
-                .coin_read_api()
-                .get_coins(sender, None, None, None)
+            if res.is_err() {
+                println!("Error: {:?}", res);
+            }
+        }
+        Some(SCommands::SubmitProofs { tids, dry_run }) => {
+            let res = submit_transaction_proofs(&light_client, &tids, dry_run)
                 .await
-                .unwrap();
-            let coin_gas = coins
-                .data
-                .into_iter()
-                .max_by_key(|coin| coin.balance)
-                .expect("no gas coins available");
-
-            // create the transaction data that will be sent to the network
-            let tx_data = TransactionData::new_programmable(
-                sender,
-                vec![coin_gas.object_ref()],
-                builder,
-                gas_budget,
-                gas_price,
-            );
-
-            // 4) sign transaction
-            let signature = keystore
-                .sign_secure(&sender, &tx_data, Intent::sui_transaction())
-                .unwrap();
-
-            // 5) execute the transaction
-            println!("Executing the transaction...");
-            let transaction_response = dwallet_client
-                .quorum_driver_api()
-                .execute_transaction_block(
-                    Transaction::from_data(tx_data, vec![signature]),
-                    SuiTransactionBlockResponseOptions::full_content(),
-                    Some(ExecuteTransactionRequestType::WaitForLocalExecution),
-                )
+                .context("proof submission error");
+            match res {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    println!("Error: {:?}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        #[cfg(feature = "server")]
+        Some(SCommands::Serve { addr }) => {
+            if let Err(e) = server::serve(light_client.clone(), resolver, addr).await {
+                println!("Error: {:?}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(SCommands::Object { id, version }) => {
+            let res = print_verified_object(&light_client, &resolver, &id, version)
                 .await
-                .unwrap();
+                .context("object verification error");
 
-            println!(
-                "Transaction executed {}",
-                transaction_response.clone().object_changes.unwrap().len()
-            );
+            if res.is_err() {
+                println!("Error: {:?}", res);
+            }
+        }
+        Some(SCommands::Events {
+            ckp,
+            event_type,
+            format,
+        }) => {
+            let res = print_verified_checkpoint_events(
+                &light_client,
+                &resolver,
+                ckp,
+                &event_type,
+                format,
+            )
+            .await
+            .context("checkpoint events verification error");
 
-            let _ = transaction_response
-                .clone()
-                .object_changes
-                .unwrap()
-                .iter()
-                .for_each(|object| println!("{}", object));
-
-            let object_changes = transaction_response.object_changes.unwrap();
-            let registry_object_change = object_changes
-                .iter()
-                .filter(|object| match object {
-                    ObjectChange::Created {
-                        sender: _,
-                        owner: _,
-                        object_type: object_type,
-                        object_id: _,
-                        version: _,
-                        digest: _,
-                    } => object_type.to_string().contains("Registry"),
-                    _ => false,
-                })
-                .next()
-                .unwrap();
-
-            let committee_object_change = object_changes
-                .iter()
-                .filter(|object| match object {
-                    ObjectChange::Created {
-                        sender: _,
-                        owner: _,
-                        object_type: object_type,
-                        object_id: _,
-                        version: _,
-                        digest: _,
-                    } => object_type.to_string().contains("EpochCommittee"),
-                    _ => false,
-                })
-                .next()
-                .unwrap();
-
-            let config_object_change = object_changes
-                .iter()
-                .filter(|object| match object {
-                    ObjectChange::Created {
-                        sender: _,
-                        owner: _,
-                        object_type: object_type,
-                        object_id: _,
-                        version: _,
-                        digest: _,
-                    } => object_type.to_string().contains("StateProofConfig"),
-                    _ => false,
-                })
-                .next()
-                .unwrap();
-
-            let registry_object_ref = registry_object_change.object_ref();
-            let committee_object_ref = committee_object_change.object_ref();
-            let config_object_ref = config_object_change.object_ref();
-
-            config.dwltn_config_object_id = config_object_ref.0.to_string();
-            config.dwltn_registry_object_id = registry_object_ref.0.to_string();
-        }
-        Some(SCommands::Sync {}) => {
-            let res = check_and_sync_checkpoints(&config)
-                .await
-                .context("check and sync error");
+            if res.is_err() {
+                println!("Error: {:?}", res);
+            }
+        }
+        Some(SCommands::DynamicField {
+            parent,
+            name_type,
+            name_json,
+        }) => {
+            let res = print_verified_dynamic_field(
+                &light_client,
+                &resolver,
+                &parent,
+                &name_type,
+                &name_json,
+            )
+            .await
+            .context("dynamic field verification error");
 
             if res.is_err() {
                 println!("Error: {:?}", res);
             }
         }
-        Some(SCommands::Transaction { tid }) => {
-            // not implemented - use TS library to submit transaction
+        Some(SCommands::Verify {
+            ckp_id,
+            committee_file,
+        }) => {
+            let res = match committee_file {
+                Some(path) => match read_committee_file(&path) {
+                    Ok(committee) => {
+                        light_client
+                            .verify_checkpoint_against(ckp_id, &committee)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                },
+                None => light_client.verify_stored_checkpoint(ckp_id),
+            };
+            match res {
+                Ok(summary) => println!(
+                    "Verified checkpoint {}\n - Epoch: {}\n - Digest: {}",
+                    ckp_id,
+                    summary.epoch(),
+                    summary.digest()
+                ),
+                Err(e) => println!("Error: {:?}", e),
+            }
+        }
+        Some(SCommands::Cache { ckp_id }) => match light_client.cache_checkpoint(ckp_id).await {
+            Ok(summary) => println!(
+                "Cached checkpoint {}\n - Epoch: {}\n - Digest: {}\n - End of epoch: {}",
+                ckp_id,
+                summary.epoch(),
+                summary.digest(),
+                summary.end_of_epoch_data.is_some()
+            ),
+            Err(e) => println!("Error: {:?}", e),
+        },
+        Some(SCommands::MigrateCheckpoints {}) => match light_client.migrate_legacy_checkpoints() {
+            Ok(imported) => println!("Imported {} checkpoints", imported),
+            Err(e) => println!("Error: {:?}", e),
+        },
+        Some(SCommands::Doctor {}) => {
+            if !run_doctor(&light_client).await {
+                std::process::exit(1);
+            }
+        }
+        Some(SCommands::SelfTest {}) => unreachable!("handled before loading the config"),
+        Some(SCommands::Committee { epoch }) => {
+            if let Err(e) = print_committee(&light_client, epoch) {
+                println!("Error: {:?}", e);
+            }
+        }
+        Some(SCommands::CommitteeDiff {
+            from_epoch,
+            to_epoch,
+        }) => {
+            if let Err(e) = print_committee_diff(&light_client, from_epoch, to_epoch) {
+                println!("Error: {:?}", e);
+            }
+        }
+        Some(SCommands::CheckCommittee { epoch }) => {
+            match check_onchain_committee(&light_client, epoch).await {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    println!("Error: {:?}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(SCommands::Export { tid, out }) => {
+            if let Err(e) = export_bundle(&light_client, &tid, &out).await {
+                println!("Error: {:?}", e);
+            }
+        }
+        Some(SCommands::VerifyBundle { bundle, format }) => {
+            if let Err(e) = verify_bundle(&light_client, &resolver, &bundle, format).await {
+                println!("Error: {:?}", e);
+            }
         }
         _ => {}
     }
-    // writing config file back
-    let file = fs::File::create(&path)
-        .unwrap_or_else(|_| panic!("Unable to open config file for writing: {}", path.display()));
-    serde_yaml::to_writer(file, &config)
-        .unwrap_or_else(|_| panic!("Failed to write config to file: {}", path.display()));
+    let cache_stats = resolver.cache_stats();
+    if cache_stats.hits + cache_stats.misses > 0 {
+        debug!(
+            hits = cache_stats.hits,
+            misses = cache_stats.misses,
+            evictions = cache_stats.evictions,
+            entries = cache_stats.entries,
+            "Package cache"
+        );
+    }
+    if let Some(update) = config_update {
+        update.apply(&mut config);
+        let file = fs::File::create(&path).unwrap_or_else(|_| {
+            panic!("Unable to open config file for writing: {}", path.display())
+        });
+        serde_yaml::to_writer(file, &config)
+            .unwrap_or_else(|_| panic!("Failed to write config to file: {}", path.display()));
+    }
 }
-
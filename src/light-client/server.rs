@@ -0,0 +1,154 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde_json::json;
+use sui_light_client::{CachingResolver, LightClient, LightClientError};
+use sui_types::{
+    base_types::SequenceNumber,
+    object::{Data, Object},
+};
+use tracing::info;
+
+use crate::{
+    decode_events, decode_move_object, parse_object_id, parse_transaction_digest, verification_json,
+};
+
+/// The handle and resolver shared by every request, so connections and caches are reused
+struct ServerState {
+    client: Arc<LightClient>,
+    resolver: CachingResolver,
+}
+
+/// A failed request, answered with its status and the error chain as JSON
+struct ApiError(StatusCode, anyhow::Error);
+
+impl ApiError {
+    fn bad_request(error: anyhow::Error) -> Self {
+        Self(StatusCode::BAD_REQUEST, error)
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        let status = match error.downcast_ref::<LightClientError>() {
+            Some(
+                LightClientError::TransactionNotFound(_) | LightClientError::CheckpointNotFound(_),
+            ) => StatusCode::NOT_FOUND,
+            // Retrying later may succeed, once checkpointed or synced
+            Some(
+                LightClientError::TransactionPending(_)
+                | LightClientError::NeedsSync { .. }
+                | LightClientError::DwalletUnavailable { .. },
+            ) => StatusCode::SERVICE_UNAVAILABLE,
+            Some(
+                LightClientError::VerificationFailed(_) | LightClientError::InvalidSignature { .. },
+            ) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(json!({ "error": format!("{:#}", self.1) }));
+        (self.0, body).into_response()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ObjectQuery {
+    /// Verify the object as it was at this version instead of the latest one
+    version: Option<u64>,
+}
+
+/// Serves `GET /verify/tx/{digest}` and `GET /verify/object/{id}` on `addr` until Ctrl-C.
+pub async fn serve(
+    client: Arc<LightClient>,
+    resolver: CachingResolver,
+    addr: SocketAddr,
+) -> anyhow::Result<()> {
+    let state = Arc::new(ServerState { client, resolver });
+    let app = Router::new()
+        .route("/verify/tx/:digest", get(verify_transaction))
+        .route("/verify/object/:id", get(verify_object))
+        .with_state(state);
+
+    info!(%addr, "Serving verifications");
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+    Ok(())
+}
+
+async fn verify_transaction(
+    State(state): State<Arc<ServerState>>,
+    Path(digest): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = parse_transaction_digest(&digest).map_err(ApiError::bad_request)?;
+    let (effects, events, committee) = state
+        .client
+        .get_verified_effects_and_events_with_committee(tid, None)
+        .await?;
+    let decoded_events = decode_events(&state.client, &state.resolver, events.as_ref()).await?;
+    Ok(Json(verification_json(
+        &effects,
+        &decoded_events,
+        Some(&committee),
+    )))
+}
+
+async fn verify_object(
+    State(state): State<Arc<ServerState>>,
+    Path(id): Path<String>,
+    Query(query): Query<ObjectQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let oid = parse_object_id(&id).map_err(ApiError::bad_request)?;
+    let object = match query.version {
+        Some(version) => {
+            state
+                .client
+                .get_verified_object_at_version(oid, SequenceNumber::from_u64(version))
+                .await?
+        }
+        None => state.client.get_verified_object(oid).await?,
+    };
+    Ok(Json(object_json(&state.resolver, &object).await?))
+}
+
+/// A verified object as a single JSON object, with its contents decoded.
+async fn object_json(
+    resolver: &CachingResolver,
+    object: &Object,
+) -> anyhow::Result<serde_json::Value> {
+    let (oid, version, digest) = object.compute_object_reference();
+    let mut output = json!({
+        "object_id": oid,
+        "version": version,
+        "digest": digest,
+        "owner": object.owner,
+    });
+    match &object.data {
+        Data::Move(move_object) => {
+            output["type"] = json!(move_object.type_().to_string());
+            output["contents"] = decode_move_object(resolver, move_object).await?;
+        }
+        Data::Package(package) => {
+            output["type"] = json!("package");
+            output["modules"] = json!(package.serialized_module_map().keys().collect::<Vec<_>>());
+        }
+    }
+    Ok(output)
+}
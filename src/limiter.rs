@@ -0,0 +1,76 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+// The tokio clock, which tests can pause and advance
+use tokio::time::Instant;
+
+/// Bounds the requests in flight to an endpoint and, optionally, the rate they start at,
+/// shared by every caller so that parallel downloads stay under the endpoint's limits.
+pub(crate) struct RequestLimiter {
+    in_flight: Semaphore,
+    bucket: Option<Mutex<TokenBucket>>,
+}
+
+/// A token bucket refilled at `rate` tokens per second up to `rate` tokens, allowing
+/// bursts of up to a second of requests.
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Takes a token, or returns how long to wait for the next one.
+    fn try_take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+impl RequestLimiter {
+    pub fn new(max_concurrency: usize, requests_per_second: Option<u32>) -> Self {
+        let bucket = requests_per_second.map(|rate| {
+            Mutex::new(TokenBucket {
+                rate: rate as f64,
+                tokens: rate as f64,
+                refilled_at: Instant::now(),
+            })
+        });
+        Self {
+            in_flight: Semaphore::new(max_concurrency.max(1)),
+            bucket,
+        }
+    }
+
+    /// Waits for a request slot and, if rate limited, a token. The request may start once
+    /// this returns and must finish before the permit is dropped.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self
+            .in_flight
+            .acquire()
+            .await
+            .expect("the request semaphore is never closed");
+        if let Some(bucket) = &self.bucket {
+            loop {
+                let wait = bucket.lock().unwrap().try_take();
+                match wait {
+                    Ok(()) => break,
+                    Err(wait) => tokio::time::sleep(wait).await,
+                }
+            }
+        }
+        permit
+    }
+}
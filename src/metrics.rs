@@ -0,0 +1,157 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::net::SocketAddr;
+
+use prometheus::{
+    register_histogram_vec_with_registry, register_int_counter_with_registry, HistogramTimer,
+    HistogramVec, IntCounter, Registry, TextEncoder,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Counters and latencies of the sync and verification operations, exported to
+/// Prometheus when `metrics_addr` is set in the config.
+pub(crate) struct Metrics {
+    registry: Registry,
+    pub checkpoints_downloaded: IntCounter,
+    pub checkpoints_verified: IntCounter,
+    pub verification_failures: IntCounter,
+    pub dwallet_submissions: IntCounter,
+    pub object_store_retries: IntCounter,
+    pub object_store_rate_limited: IntCounter,
+    pub rpc_clients_built: IntCounter,
+    operation_latency: HistogramVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("light_client".to_string()), None).unwrap();
+        Self {
+            checkpoints_downloaded: register_int_counter_with_registry!(
+                "checkpoints_downloaded",
+                "Checkpoints downloaded from the object store",
+                registry
+            )
+            .unwrap(),
+            checkpoints_verified: register_int_counter_with_registry!(
+                "checkpoints_verified",
+                "End of epoch checkpoints verified against their committee",
+                registry
+            )
+            .unwrap(),
+            verification_failures: register_int_counter_with_registry!(
+                "verification_failures",
+                "Checkpoints, transactions and objects that failed verification",
+                registry
+            )
+            .unwrap(),
+            dwallet_submissions: register_int_counter_with_registry!(
+                "dwallet_submissions",
+                "Epoch committees submitted to the dWallet network",
+                registry
+            )
+            .unwrap(),
+            object_store_retries: register_int_counter_with_registry!(
+                "object_store_retries",
+                "Object store requests retried after a failure",
+                registry
+            )
+            .unwrap(),
+            object_store_rate_limited: register_int_counter_with_registry!(
+                "object_store_rate_limited",
+                "Object store requests rejected with 429 Too Many Requests",
+                registry
+            )
+            .unwrap(),
+            rpc_clients_built: register_int_counter_with_registry!(
+                "rpc_clients_built",
+                "Full node and dWallet JSON-RPC clients connected",
+                registry
+            )
+            .unwrap(),
+            operation_latency: register_histogram_vec_with_registry!(
+                "operation_latency_seconds",
+                "Latency of the download and verification operations",
+                &["operation"],
+                registry
+            )
+            .unwrap(),
+            registry,
+        }
+    }
+
+    /// Starts timing `operation`, observed in its latency histogram when dropped
+    pub fn start_timer(&self, operation: &str) -> HistogramTimer {
+        self.operation_latency
+            .with_label_values(&[operation])
+            .start_timer()
+    }
+
+    /// Serves the metrics in the Prometheus text format at `/metrics` on `addr`.
+    ///
+    /// A plain HTTP/1.1 responder, so the exporter does not need the HTTP server stack of
+    /// the `server` feature.
+    pub fn start_exporter(&self, addr: SocketAddr) -> JoinHandle<()> {
+        let registry = self.registry.clone();
+        info!(%addr, "Serving Prometheus metrics");
+        tokio::spawn(async move {
+            let listener = match TcpListener::bind(addr).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    warn!(%addr, error = %e, "Metrics exporter stopped");
+                    return;
+                }
+            };
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        debug!(error = %e, "Failed to accept a metrics connection");
+                        continue;
+                    }
+                };
+                let registry = registry.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = answer_metrics_request(stream, &registry).await {
+                        debug!(error = %e, "Failed to answer a metrics request");
+                    }
+                });
+            }
+        })
+    }
+}
+
+/// Answers the HTTP request on `stream` with the metrics of `registry` for `GET /metrics`,
+/// or with a 404 for any other path, then closes the connection.
+async fn answer_metrics_request(mut stream: TcpStream, registry: &Registry) -> std::io::Result<()> {
+    let (read, mut write) = stream.split();
+    let mut reader = BufReader::new(read);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    // Read the headers up to the blank line ending them before answering
+    let mut header = String::new();
+    while reader.read_line(&mut header).await? > 2 {
+        header.clear();
+    }
+
+    let (status, body) = if request_line.starts_with("GET /metrics ") {
+        let body = TextEncoder::new()
+            .encode_to_string(&registry.gather())
+            .unwrap_or_else(|e| format!("# failed to encode metrics: {}", e));
+        ("200 OK", body)
+    } else {
+        ("404 Not Found", String::new())
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    write.write_all(response.as_bytes()).await?;
+    write.shutdown().await
+}
@@ -0,0 +1,52 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use async_trait::async_trait;
+use sui_types::{
+    base_types::{ObjectID, ObjectRef},
+    digests::TransactionDigest,
+    messages_checkpoint::CertifiedCheckpointSummary,
+};
+
+use crate::proof::TransactionProof;
+
+/// Stands in for the Sui full node and the dWallet network in a [`LightClient`] set up
+/// with [`LightClient::with_network_stub`], e.g. to run a sync or a proof submission in
+/// tests against checkpoints served from an in-memory object store.
+///
+/// Checkpoints are still downloaded from the object store and verified as usual, only
+/// the lookups and submissions below are answered by the stub.
+///
+/// [`LightClient`]: crate::LightClient
+/// [`LightClient::with_network_stub`]: crate::LightClient::with_network_stub
+#[async_trait]
+pub trait NetworkStub: Send + Sync {
+    /// The sequence number of the latest checkpoint of the Sui network
+    async fn latest_checkpoint(&self) -> anyhow::Result<u64>;
+
+    /// The sequence number of the checkpoint that includes `tid`
+    async fn transaction_checkpoint(&self, tid: TransactionDigest) -> anyhow::Result<u64>;
+
+    /// The highest epoch whose committee is registered on the dWallet network, or 0
+    async fn highest_registered_epoch(&self) -> anyhow::Result<u64>;
+
+    /// The id of the committee object of `epoch` registered on the dWallet network
+    async fn epoch_committee_id(&self, epoch: u64) -> anyhow::Result<ObjectID>;
+
+    /// Registers the committee that end-of-epoch checkpoint `summary` hands over to,
+    /// returning the created committee object, or `None` in a dry run
+    async fn submit_committee(
+        &self,
+        summary: &CertifiedCheckpointSummary,
+        prev_committee_object_ref: Option<ObjectRef>,
+        dry_run: bool,
+    ) -> anyhow::Result<Option<ObjectRef>>;
+
+    /// Submits verified transaction proofs, each with the committee object of its epoch,
+    /// returning the digest of the dWallet transaction, or `None` in a dry run
+    async fn submit_proofs(
+        &self,
+        proofs: &[(TransactionProof, ObjectID)],
+        dry_run: bool,
+    ) -> anyhow::Result<Option<TransactionDigest>>;
+}
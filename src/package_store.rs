@@ -0,0 +1,282 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::future::{join_all, BoxFuture, FutureExt};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::annotated_value::MoveTypeLayout;
+use move_core_types::language_storage::TypeTag;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use sui_package_resolver::error::Error as ResolverError;
+use sui_package_resolver::Result as ResolverResult;
+use sui_package_resolver::{Package, PackageStore, Resolver};
+use sui_types::object::Object;
+use sui_types::transaction::ProgrammableTransaction;
+use tokio::sync::OnceCell;
+use tracing::info;
+
+use crate::client::LightClient;
+
+/// Hits and misses of the package cache of a [`RemotePackageStore`] since it was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackageCacheStats {
+    /// Requests served by a cached package or by a fetch already in flight
+    pub hits: u64,
+    /// Requests that fetched the package from the full node
+    pub misses: u64,
+    /// Packages dropped to stay within `max_cache_entries`
+    pub evictions: u64,
+    /// Packages cached now
+    pub entries: usize,
+}
+
+impl fmt::Display for PackageCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses, {} evictions, {} packages cached",
+            self.hits, self.misses, self.evictions, self.entries
+        )
+    }
+}
+
+/// The counters behind [`PackageCacheStats`], shared with the [`CachingResolver`] owning
+/// the store.
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    entries: AtomicU64,
+}
+
+impl CacheCounters {
+    fn stats(&self) -> PackageCacheStats {
+        PackageCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.entries.load(Ordering::Relaxed) as usize,
+        }
+    }
+}
+
+/// A cached package, or the fetch of it in flight, with the tick of its last use.
+struct CacheEntry {
+    package: Arc<OnceCell<Arc<Package>>>,
+    last_used: u64,
+}
+
+/// The packages cached by a [`RemotePackageStore`], evicting the least recently used
+/// one past `max_entries`.
+#[derive(Default)]
+struct PackageCache {
+    entries: HashMap<AccountAddress, CacheEntry>,
+    tick: u64,
+    max_entries: Option<usize>,
+}
+
+impl PackageCache {
+    /// Returns the entry of `id` marked as just used, inserting an empty one if missing.
+    fn get_or_insert(
+        &mut self,
+        id: AccountAddress,
+        counters: &CacheCounters,
+    ) -> Arc<OnceCell<Arc<Package>>> {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.last_used = tick;
+            return entry.package.clone();
+        }
+
+        if let Some(max_entries) = self.max_entries {
+            while self.entries.len() >= max_entries {
+                let Some(oldest) = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(id, _)| *id)
+                else {
+                    break;
+                };
+                // A fetch in flight keeps going for the requests already waiting on it
+                self.entries.remove(&oldest);
+                counters.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let package = Arc::new(OnceCell::new());
+        self.entries.insert(
+            id,
+            CacheEntry {
+                package: package.clone(),
+                last_used: tick,
+            },
+        );
+        counters
+            .entries
+            .store(self.entries.len() as u64, Ordering::Relaxed);
+        package
+    }
+}
+
+/// A package store that fetches packages from the full node and, unless created with
+/// [`RemotePackageStore::for_resolution`], authenticates them with the light client before
+/// caching them.
+///
+/// Packages are fetched concurrently: each fetched package prefetches its dependencies,
+/// and concurrent requests for the same package share a single fetch. The cache holds at
+/// most `max_cache_entries` packages of the client config, evicting the least recently used
+/// one, and counts its hits and misses in [`RemotePackageStore::cache_stats`].
+///
+/// Authenticating a package verifies the effects of the transaction that published it,
+/// which does not resolve any type, so an authenticated fetch never comes back to the
+/// store. Code resolving types while it verifies effects must use a store created with
+/// [`RemotePackageStore::for_resolution`] instead: its packages are trusted to decode
+/// values whose bytes were verified, and for nothing else.
+pub struct RemotePackageStore {
+    client: Arc<LightClient>,
+    authenticate: bool,
+    cache: Mutex<PackageCache>,
+    counters: Arc<CacheCounters>,
+}
+impl RemotePackageStore {
+    pub fn new(client: Arc<LightClient>) -> Self {
+        Self::with_authentication(client, true)
+    }
+
+    /// A store fetching packages without authenticating them, saving a full checkpoint
+    /// download per package, for resolving types where the authenticated path could
+    /// recurse.
+    pub fn for_resolution(client: Arc<LightClient>) -> Self {
+        Self::with_authentication(client, false)
+    }
+
+    fn with_authentication(client: Arc<LightClient>, authenticate: bool) -> Self {
+        let cache = PackageCache {
+            max_entries: client.config().max_cache_entries,
+            ..Default::default()
+        };
+        Self {
+            client,
+            authenticate,
+            cache: Mutex::new(cache),
+            counters: Arc::default(),
+        }
+    }
+
+    /// Hits and misses of the package cache so far
+    pub fn cache_stats(&self) -> PackageCacheStats {
+        self.counters.stats()
+    }
+
+    /// Returns the cached package `id`, fetching it on the first request. A failed fetch
+    /// is not cached, so a later request tries again.
+    fn fetch_cached(&self, id: AccountAddress) -> BoxFuture<'_, ResolverResult<Arc<Package>>> {
+        async move {
+            let cell = self.cache.lock().unwrap().get_or_insert(id, &self.counters);
+            let mut fetched = false;
+            let package = cell
+                .get_or_try_init(|| {
+                    fetched = true;
+                    self.fetch_remote(id)
+                })
+                .await
+                .map(Arc::clone);
+            let counter = if fetched {
+                &self.counters.misses
+            } else {
+                &self.counters.hits
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+            package
+        }
+        .boxed()
+    }
+
+    async fn fetch_remote(&self, id: AccountAddress) -> ResolverResult<Arc<Package>> {
+        info!(%id, "Fetch Package");
+
+        let object = if self.authenticate {
+            self.client.get_verified_object(id.into()).await
+        } else {
+            self.client.get_object_for_resolution(id.into()).await
+        };
+        let object: Object = object
+            .with_context(|| format!("package {} not found on full node", id))
+            .map_err(|e| ResolverError::Store {
+                store: "RemotePackageStore",
+                source: e.into(),
+            })?;
+
+        // The resolver requests the dependencies one at a time, so fetch them all at once
+        // here and let its requests hit the cache. Their errors surface on those requests.
+        if let Some(package) = object.data.try_as_package() {
+            let dependencies = package
+                .linkage_table()
+                .values()
+                .map(|upgrade| AccountAddress::from(upgrade.upgraded_id))
+                .filter(|dependency| *dependency != id);
+            join_all(dependencies.map(|dependency| self.fetch_cached(dependency))).await;
+        }
+
+        Ok(Arc::new(Package::read_from_object(&object)?))
+    }
+}
+
+#[async_trait]
+impl PackageStore for RemotePackageStore {
+    /// Read package contents. Fails if `id` is not an object, not a package, or is malformed in
+    /// some way.
+    async fn fetch(&self, id: AccountAddress) -> ResolverResult<Arc<Package>> {
+        self.fetch_cached(id).await
+    }
+}
+
+/// A resolver over a [`RemotePackageStore`] that also caches the resolved type layouts, so
+/// decoding many values of the same type resolves its layout only once.
+pub struct CachingResolver {
+    resolver: Resolver<RemotePackageStore>,
+    layouts: Mutex<HashMap<TypeTag, MoveTypeLayout>>,
+    package_counters: Arc<CacheCounters>,
+}
+impl CachingResolver {
+    pub fn new(store: RemotePackageStore) -> Self {
+        Self {
+            package_counters: store.counters.clone(),
+            resolver: Resolver::new(store),
+            layouts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hits and misses of the package cache of the store, see
+    /// [`RemotePackageStore::cache_stats`].
+    pub fn cache_stats(&self) -> PackageCacheStats {
+        self.package_counters.stats()
+    }
+
+    /// Return the annotated layout of `tag`, resolving it through the package store on the
+    /// first request for that type.
+    pub async fn type_layout(&self, tag: TypeTag) -> ResolverResult<MoveTypeLayout> {
+        if let Some(layout) = self.layouts.lock().unwrap().get(&tag) {
+            return Ok(layout.clone());
+        }
+
+        let layout = self.resolver.type_layout(tag.clone()).await?;
+        self.layouts.lock().unwrap().insert(tag, layout.clone());
+        Ok(layout)
+    }
+
+    /// Return the annotated layouts of the pure inputs of `tx`, from the parameter types of
+    /// the commands they are passed to, or `None` for a pure input no command uses.
+    pub async fn pure_input_layouts(
+        &self,
+        tx: &ProgrammableTransaction,
+    ) -> ResolverResult<Vec<Option<MoveTypeLayout>>> {
+        self.resolver.pure_input_layouts(tx).await
+    }
+}
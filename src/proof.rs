@@ -0,0 +1,95 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use sui_rest_api::CheckpointData;
+use sui_types::{
+    committee::Committee,
+    digests::TransactionDigest,
+    effects::TransactionEffectsAPI,
+    full_checkpoint_content::CheckpointTransaction,
+    messages_checkpoint::{CertifiedCheckpointSummary, CheckpointContents},
+};
+
+use crate::error::LightClientError;
+use crate::verifier::{check_events_digest, verify_summary_with_contents};
+
+/// A stored proof that a transaction is part of a checkpoint: the signed summary, the
+/// committee that signed it, the contents it commits to and the matched transaction.
+///
+/// Unlike a [`ProofBundle`](crate::ProofBundle) it does not chain the committee to
+/// genesis, the caller checks it against a committee it already trusts.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TransactionProof {
+    pub checkpoint_summary: CertifiedCheckpointSummary,
+    pub committee: Committee,
+    pub checkpoint_contents: CheckpointContents,
+    pub transaction: CheckpointTransaction,
+}
+
+impl TransactionProof {
+    pub fn transaction_digest(&self) -> &TransactionDigest {
+        self.transaction.effects.transaction_digest()
+    }
+}
+
+/// Verifies a full checkpoint against `committee` and extracts the proof of transaction
+/// `tid` from it.
+pub fn build_transaction_proof(
+    checkpoint: &CheckpointData,
+    committee: &Committee,
+    tid: TransactionDigest,
+) -> Result<TransactionProof, LightClientError> {
+    let summary = &checkpoint.checkpoint_summary;
+    let transaction = checkpoint
+        .transactions
+        .iter()
+        .find(|tx| *tx.effects.transaction_digest() == tid)
+        .ok_or_else(|| {
+            LightClientError::VerificationFailed(format!(
+                "Transaction {} not found in checkpoint {}",
+                tid, summary.sequence_number
+            ))
+        })?;
+
+    let proof = TransactionProof {
+        checkpoint_summary: summary.clone(),
+        committee: committee.clone(),
+        checkpoint_contents: checkpoint.checkpoint_contents.clone(),
+        transaction: transaction.clone(),
+    };
+    verify_transaction_proof(&proof, committee)?;
+    Ok(proof)
+}
+
+/// Re-checks a proof offline: the summary is signed by `committee`, which must be the one
+/// recorded in the proof, the contents are the ones it commits to, and they authenticate
+/// the effects and events of the transaction.
+pub fn verify_transaction_proof(
+    proof: &TransactionProof,
+    committee: &Committee,
+) -> Result<(), LightClientError> {
+    let summary = &proof.checkpoint_summary;
+    if proof.committee != *committee {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Proof of checkpoint {} records the committee of epoch {}, not the trusted \
+             committee of epoch {}",
+            summary.sequence_number, proof.committee.epoch, committee.epoch
+        )));
+    }
+    verify_summary_with_contents(summary, &proof.checkpoint_contents, committee)?;
+
+    // The effects must be the ones authenticated in the contents
+    let tx = &proof.transaction;
+    let digests = tx.effects.execution_digests();
+    if !proof
+        .checkpoint_contents
+        .iter()
+        .any(|contents_digests| *contents_digests == digests)
+    {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Transaction {} not found in checkpoint {} contents",
+            digests.transaction, summary.sequence_number
+        )));
+    }
+    check_events_digest(tx)
+}
@@ -53,7 +53,16 @@ pub struct TxDataResponse {
 }
 
 pub async fn get_tx_data(payload: Query<TxDataRequest>) -> impl IntoResponse {
-    let tid = TransactionDigest::from_str(&payload.tx_id).unwrap();
+    let tid = match TransactionDigest::from_str(&payload.tx_id) {
+        Result::Ok(tid) => tid,
+        Err(e) => {
+            let message = format!(
+                "invalid transaction digest '{}': expected base58-encoded 32-byte digest: {}",
+                payload.tx_id, e
+            );
+            return (StatusCode::BAD_REQUEST, message).into_response();
+        }
+    };
 
     // TOOD don't hardcode
     let sui_client: Arc<sui_sdk::SuiClient> = Arc::new(
@@ -0,0 +1,523 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::{collections::BTreeMap, fmt};
+
+use sui_rest_api::CheckpointData;
+use sui_types::{
+    base_types::{AuthorityName, ObjectID, ObjectRef, SequenceNumber, SuiAddress},
+    committee::{Committee, StakeUnit, TOTAL_VOTING_POWER},
+    digests::ObjectDigest,
+    digests::TransactionDigest,
+    effects::{TransactionEffects, TransactionEffectsAPI, TransactionEvents},
+    full_checkpoint_content::CheckpointTransaction,
+    messages_checkpoint::{
+        CertifiedCheckpointSummary, CheckpointContents, CheckpointSummary, EndOfEpochData,
+    },
+    object::Owner,
+    transaction::{TransactionData, TransactionDataAPI},
+};
+
+use crate::error::LightClientError;
+
+/// Why a checkpoint certificate failed to verify against a committee, carried by
+/// [`LightClientError::InvalidSignature`].
+///
+/// Tells a certificate checked against the committee of another epoch, which calls for
+/// syncing the missing committee, apart from one lacking stake or with a bad signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureDiagnostic {
+    pub checkpoint: u64,
+    /// Epoch the aggregated signature claims
+    pub signature_epoch: u64,
+    /// Epoch of the committee the signature was checked against
+    pub committee_epoch: u64,
+    /// Stake of the committee members in the signers bitmap
+    pub signed_stake: u64,
+    pub quorum_threshold: u64,
+}
+
+impl SignatureDiagnostic {
+    pub fn new(summary: &CertifiedCheckpointSummary, committee: &Committee) -> Self {
+        let auth_sig = summary.auth_sig();
+        let signed_stake = auth_sig
+            .signers_map
+            .iter()
+            .filter_map(|index| committee.authority_by_index(index))
+            .map(|name| committee.weight(name))
+            .sum();
+        Self {
+            checkpoint: summary.sequence_number,
+            signature_epoch: auth_sig.epoch,
+            committee_epoch: committee.epoch,
+            signed_stake,
+            quorum_threshold: committee.quorum_threshold(),
+        }
+    }
+
+    /// The likely reason the verification failed
+    pub fn cause(&self) -> String {
+        if self.signature_epoch != self.committee_epoch {
+            format!(
+                "wrong committee, sync the committee of epoch {}",
+                self.signature_epoch
+            )
+        } else if self.signed_stake < self.quorum_threshold {
+            "insufficient stake".to_string()
+        } else {
+            "invalid signature".to_string()
+        }
+    }
+}
+
+impl fmt::Display for SignatureDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to verify checkpoint {}: signed in epoch {} but checked against the \
+             committee of epoch {}, signed stake {} of quorum threshold {} ({})",
+            self.checkpoint,
+            self.signature_epoch,
+            self.committee_epoch,
+            self.signed_stake,
+            self.quorum_threshold,
+            self.cause()
+        )
+    }
+}
+
+/// Builds the committee of `epoch` from the stake of each validator.
+///
+/// The stakes must sum to the total voting power the quorum threshold is computed over, as
+/// they do in chain data; any other set, e.g. an empty one, is rejected rather than scaled.
+pub fn committee_from_stakes(
+    epoch: u64,
+    stakes: impl IntoIterator<Item = (AuthorityName, StakeUnit)>,
+) -> Result<Committee, LightClientError> {
+    let stakes: BTreeMap<AuthorityName, StakeUnit> = stakes.into_iter().collect();
+    let total = stakes
+        .values()
+        .try_fold(0 as StakeUnit, |total, stake| total.checked_add(*stake));
+    if total != Some(TOTAL_VOTING_POWER) {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Committee of epoch {} has a total stake of {}, expected {}",
+            epoch,
+            total.map_or("more than u64::MAX".to_string(), |total| total.to_string()),
+            TOTAL_VOTING_POWER
+        )));
+    }
+    Ok(Committee::new(epoch, stakes))
+}
+
+/// How a validator differs between two committees, as listed by [`diff_committees`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitteeChange {
+    Added {
+        name: AuthorityName,
+        stake: StakeUnit,
+    },
+    Removed {
+        name: AuthorityName,
+        stake: StakeUnit,
+    },
+    StakeChanged {
+        name: AuthorityName,
+        from: StakeUnit,
+        to: StakeUnit,
+    },
+}
+
+impl fmt::Display for CommitteeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { name, stake } => write!(f, "Added: {} (stake {})", name, stake),
+            Self::Removed { name, stake } => write!(f, "Removed: {} (stake {})", name, stake),
+            Self::StakeChanged { name, from, to } => {
+                write!(f, "Stake changed: {} {} -> {}", name, from, to)
+            }
+        }
+    }
+}
+
+/// Lists the validators added, removed or with a changed stake going from committee `from`
+/// to committee `to`, empty if both have the same members and stakes. The epochs of the
+/// committees are not compared.
+pub fn diff_committees(from: &Committee, to: &Committee) -> Vec<CommitteeChange> {
+    let from: BTreeMap<_, _> = from.voting_rights.iter().copied().collect();
+    let to: BTreeMap<_, _> = to.voting_rights.iter().copied().collect();
+
+    let mut changes = vec![];
+    for (name, stake) in &to {
+        match from.get(name) {
+            None => changes.push(CommitteeChange::Added {
+                name: *name,
+                stake: *stake,
+            }),
+            Some(old_stake) if old_stake != stake => changes.push(CommitteeChange::StakeChanged {
+                name: *name,
+                from: *old_stake,
+                to: *stake,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (name, stake) in &from {
+        if !to.contains_key(name) {
+            changes.push(CommitteeChange::Removed {
+                name: *name,
+                stake: *stake,
+            });
+        }
+    }
+    changes
+}
+
+/// Checks the aggregated signature of a summary is from the epoch the summary claims, as
+/// a tampered or malformed file may pair a summary with a signature of another epoch.
+pub(crate) fn check_signature_epoch(
+    summary: &CertifiedCheckpointSummary,
+) -> Result<(), LightClientError> {
+    let signature_epoch = summary.auth_sig().epoch;
+    if signature_epoch != summary.data().epoch {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Checkpoint {} claims epoch {} but is signed in epoch {}",
+            summary.sequence_number,
+            summary.data().epoch,
+            signature_epoch
+        )));
+    }
+    Ok(())
+}
+
+/// Verifies a checkpoint summary, end-of-epoch or not, against the committee of its epoch.
+pub fn verify_checkpoint_summary(
+    summary: &CertifiedCheckpointSummary,
+    committee: &Committee,
+) -> Result<(), LightClientError> {
+    check_signature_epoch(summary)?;
+    summary
+        .clone()
+        .try_into_verified(committee)
+        .map_err(|source| LightClientError::InvalidSignature {
+            diagnostic: SignatureDiagnostic::new(summary, committee),
+            source: Box::new(source),
+        })?;
+    Ok(())
+}
+
+/// Verifies an end-of-epoch checkpoint summary against the committee of its epoch
+/// and returns the committee of the next epoch.
+pub fn verify_end_of_epoch_summary(
+    summary: &CertifiedCheckpointSummary,
+    committee: &Committee,
+) -> Result<Committee, LightClientError> {
+    verify_checkpoint_summary(summary, committee)?;
+    next_committee_from_summary(summary)
+}
+
+/// Returns the committee of the next epoch an end-of-epoch checkpoint hands over to,
+/// without verifying the checkpoint.
+pub fn next_committee_from_summary(
+    summary: &CheckpointSummary,
+) -> Result<Committee, LightClientError> {
+    let EndOfEpochData {
+        next_epoch_committee,
+        ..
+    } = end_of_epoch_data(summary)?;
+    committee_from_stakes(summary.epoch + 1, next_epoch_committee.iter().cloned())
+}
+
+/// Returns the protocol version the next epoch runs, as announced by an end-of-epoch
+/// checkpoint, without verifying the checkpoint.
+pub fn next_protocol_version_from_summary(
+    summary: &CheckpointSummary,
+) -> Result<u64, LightClientError> {
+    Ok(end_of_epoch_data(summary)?
+        .next_epoch_protocol_version
+        .as_u64())
+}
+
+fn end_of_epoch_data(summary: &CheckpointSummary) -> Result<&EndOfEpochData, LightClientError> {
+    summary.end_of_epoch_data.as_ref().ok_or_else(|| {
+        LightClientError::VerificationFailed(format!(
+            "Checkpoint {} is not an end-of-epoch checkpoint",
+            summary.sequence_number
+        ))
+    })
+}
+
+/// Checks that a list of end-of-epoch checkpoints closes consecutive epochs starting at
+/// `first_epoch`, so that each committee can be derived from the previous checkpoint.
+pub fn check_contiguous_epochs(
+    summaries: &[CertifiedCheckpointSummary],
+    first_epoch: u64,
+) -> Result<(), LightClientError> {
+    let mut expected_epoch = first_epoch;
+    let mut prev_seq = None;
+    for summary in summaries {
+        let epoch = summary.epoch();
+        match prev_seq {
+            _ if epoch == expected_epoch => {}
+            None => {
+                return Err(LightClientError::VerificationFailed(format!(
+                    "checkpoint list starts at epoch {} with checkpoint {}, expected epoch {}",
+                    epoch, summary.sequence_number, expected_epoch
+                )))
+            }
+            Some(prev_seq) if epoch > expected_epoch => {
+                return Err(LightClientError::VerificationFailed(format!(
+                    "gap between epoch {} and {} in checkpoint list, between checkpoints {} and {}",
+                    expected_epoch - 1,
+                    epoch,
+                    prev_seq,
+                    summary.sequence_number
+                )))
+            }
+            Some(prev_seq) => {
+                return Err(LightClientError::VerificationFailed(format!(
+                    "epoch {} out of order in checkpoint list, checkpoint {} follows {}",
+                    epoch, summary.sequence_number, prev_seq
+                )))
+            }
+        }
+        expected_epoch = epoch + 1;
+        prev_seq = Some(summary.sequence_number);
+    }
+    Ok(())
+}
+
+/// A transaction of a verified checkpoint with its authenticated effects and events
+pub type VerifiedTransaction = (
+    TransactionDigest,
+    TransactionEffects,
+    Option<TransactionEvents>,
+);
+
+/// Checks `contents` are the ones the summary commits to, and the summary is signed by
+/// `committee`.
+pub(crate) fn verify_summary_with_contents(
+    summary: &CertifiedCheckpointSummary,
+    contents: &CheckpointContents,
+    committee: &Committee,
+) -> Result<(), LightClientError> {
+    // Check the contents are the ones the summary commits to, so transactions are
+    // matched against contents the committee signed
+    if *contents.digest() != summary.content_digest {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Checkpoint {} contents digest does not match its summary",
+            summary.sequence_number
+        )));
+    }
+
+    // Verify the checkpoint summary using the committee
+    summary
+        .verify_with_contents(committee, Some(contents))
+        .map_err(|source| LightClientError::InvalidSignature {
+            diagnostic: SignatureDiagnostic::new(summary, committee),
+            source: Box::new(source),
+        })
+}
+
+/// Checks the events of a transaction are the ones its effects commit to.
+pub(crate) fn check_events_digest(tx: &CheckpointTransaction) -> Result<(), LightClientError> {
+    let events_digest = tx.events.as_ref().map(|events| events.digest());
+    if events_digest.as_ref() != tx.effects.events_digest() {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Events digest of transaction {} does not match",
+            tx.effects.transaction_digest()
+        )));
+    }
+    Ok(())
+}
+
+/// Verifies a full checkpoint against the committee of its epoch and returns the
+/// authenticated effects and events of the transaction `tid` it contains.
+pub fn extract_verified_effects_and_events(
+    checkpoint: &CheckpointData,
+    committee: &Committee,
+    tid: TransactionDigest,
+) -> Result<(TransactionEffects, Option<TransactionEvents>), LightClientError> {
+    let matching_tx = find_verified_transaction(checkpoint, committee, tid)?;
+    Ok((matching_tx.effects.clone(), matching_tx.events.clone()))
+}
+
+/// The sender and gas payment of a transaction, read from its transaction data once the
+/// checkpoint contents authenticate it, so they are covered by the same committee
+/// signature as its effects.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct VerifiedSenderAndGas {
+    pub sender: SuiAddress,
+    /// Owner of the gas coins, the sponsor of a sponsored transaction
+    pub gas_owner: SuiAddress,
+    pub gas_payment: Vec<ObjectRef>,
+    pub gas_price: u64,
+    pub gas_budget: u64,
+}
+
+/// Verifies a full checkpoint against the committee of its epoch and returns the
+/// authenticated sender and gas payment of the transaction `tid` it contains.
+///
+/// Unlike the effects, the transaction data is only authenticated through its digest
+/// listed in the checkpoint contents, which is checked here.
+pub fn extract_verified_sender_and_gas(
+    checkpoint: &CheckpointData,
+    committee: &Committee,
+    tid: TransactionDigest,
+) -> Result<VerifiedSenderAndGas, LightClientError> {
+    let data = extract_verified_transaction_data(checkpoint, committee, tid)?;
+    let gas_data = data.gas_data();
+    Ok(VerifiedSenderAndGas {
+        sender: data.sender(),
+        gas_owner: gas_data.owner,
+        gas_payment: gas_data.payment.clone(),
+        gas_price: gas_data.price,
+        gas_budget: gas_data.budget,
+    })
+}
+
+/// Verifies a full checkpoint against the committee of its epoch and returns the data of
+/// the transaction `tid` it contains, e.g. its programmable transaction commands, checked
+/// against the transaction digest listed in the checkpoint contents.
+pub fn extract_verified_transaction_data(
+    checkpoint: &CheckpointData,
+    committee: &Committee,
+    tid: TransactionDigest,
+) -> Result<TransactionData, LightClientError> {
+    let matching_tx = find_verified_transaction(checkpoint, committee, tid)?;
+    if *matching_tx.transaction.digest() != tid {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Data of transaction {} has digest {}",
+            tid,
+            matching_tx.transaction.digest()
+        )));
+    }
+    Ok(matching_tx.transaction.transaction_data().clone())
+}
+
+/// Verifies the summary and contents of a full checkpoint and returns the transaction
+/// `tid`, whose effects and events the contents authenticate.
+fn find_verified_transaction<'a>(
+    checkpoint: &'a CheckpointData,
+    committee: &Committee,
+    tid: TransactionDigest,
+) -> Result<&'a CheckpointTransaction, LightClientError> {
+    let summary = &checkpoint.checkpoint_summary;
+    verify_summary_with_contents(summary, &checkpoint.checkpoint_contents, committee)?;
+
+    // Check the validity of the transaction
+    let contents = &checkpoint.checkpoint_contents;
+    let (matching_tx, _) = checkpoint
+        .transactions
+        .iter()
+        .zip(contents.iter())
+        // Note that we get the digest of the effects to ensure this is
+        // indeed the correct effects that are authenticated in the contents.
+        .find(|(tx, digest)| {
+            tx.effects.execution_digests() == **digest && digest.transaction == tid
+        })
+        .ok_or_else(|| {
+            LightClientError::VerificationFailed(format!(
+                "Transaction {} not found in checkpoint {} contents",
+                tid, summary.sequence_number
+            ))
+        })?;
+
+    // Check the events are all correct.
+    check_events_digest(matching_tx)?;
+    Ok(matching_tx)
+}
+
+/// Verifies a full checkpoint against the committee of its epoch and returns the
+/// authenticated effects and events of every transaction it contains, in order.
+pub fn verify_checkpoint_transactions(
+    checkpoint: &CheckpointData,
+    committee: &Committee,
+) -> Result<Vec<VerifiedTransaction>, LightClientError> {
+    let summary = &checkpoint.checkpoint_summary;
+    verify_summary_with_contents(summary, &checkpoint.checkpoint_contents, committee)?;
+
+    let contents = &checkpoint.checkpoint_contents;
+    if checkpoint.transactions.len() != contents.size() {
+        return Err(LightClientError::VerificationFailed(format!(
+            "Checkpoint {} has {} transactions but its contents list {}",
+            summary.sequence_number,
+            checkpoint.transactions.len(),
+            contents.size()
+        )));
+    }
+
+    let mut verified = Vec::with_capacity(contents.size());
+    for (tx, digests) in checkpoint.transactions.iter().zip(contents.iter()) {
+        // The effects must be the ones authenticated in the contents
+        if tx.effects.execution_digests() != *digests {
+            return Err(LightClientError::VerificationFailed(format!(
+                "Effects of transaction {} do not match checkpoint {} contents",
+                digests.transaction, summary.sequence_number
+            )));
+        }
+        check_events_digest(tx)?;
+        verified.push((digests.transaction, tx.effects.clone(), tx.events.clone()));
+    }
+    Ok(verified)
+}
+
+/// An object a transaction changed, at the version and digest it has after the
+/// transaction, with its owner unless the object is gone.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ChangedObject {
+    pub object_id: ObjectID,
+    pub version: SequenceNumber,
+    pub digest: ObjectDigest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Owner>,
+}
+
+impl ChangedObject {
+    fn new((object_id, version, digest): ObjectRef, owner: Option<Owner>) -> Self {
+        Self {
+            object_id,
+            version,
+            digest,
+            owner,
+        }
+    }
+}
+
+/// What a transaction did to each object it touched, according to its effects.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ObjectChangeSummary {
+    pub created: Vec<ChangedObject>,
+    pub mutated: Vec<ChangedObject>,
+    pub deleted: Vec<ChangedObject>,
+    /// Objects wrapped into another object, no longer accessible by id
+    pub wrapped: Vec<ChangedObject>,
+    /// Wrapped objects taken out of their parent again
+    pub unwrapped: Vec<ChangedObject>,
+    /// Wrapped objects deleted without being unwrapped first
+    pub unwrapped_then_deleted: Vec<ChangedObject>,
+}
+
+/// Classifies the objects changed by a transaction according to its effects, to be trusted
+/// only when the effects are verified, e.g. by [`extract_verified_effects_and_events`].
+pub fn classify_object_changes(effects: &TransactionEffects) -> ObjectChangeSummary {
+    let with_owner = |changes: Vec<(ObjectRef, Owner)>| {
+        changes
+            .into_iter()
+            .map(|(object_ref, owner)| ChangedObject::new(object_ref, Some(owner)))
+            .collect()
+    };
+    let without_owner = |changes: Vec<ObjectRef>| {
+        changes
+            .into_iter()
+            .map(|object_ref| ChangedObject::new(object_ref, None))
+            .collect()
+    };
+    ObjectChangeSummary {
+        created: with_owner(effects.created()),
+        mutated: with_owner(effects.mutated()),
+        deleted: without_owner(effects.deleted()),
+        wrapped: without_owner(effects.wrapped()),
+        unwrapped: with_owner(effects.unwrapped()),
+        unwrapped_then_deleted: without_owner(effects.unwrapped_then_deleted()),
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use sui_light_client::{genesis_committee, LightClient, ProofBundle};
+
+mod common;
+
+use common::example_config;
+
+#[tokio::test]
+#[ignore = "downloads the last checkpoint of epoch 0 from the testnet object store"]
+async fn bundle_verifies_after_a_round_trip() {
+    let config = example_config();
+    let client = LightClient::new(config.clone()).unwrap();
+
+    // The last checkpoint of epoch 0 needs no end of epoch checkpoints before it
+    let seq = client.query_last_checkpoint_of_epoch(0).await.unwrap();
+    let full_checkpoint = client.get_full_checkpoint(seq).await.unwrap();
+    let transaction = full_checkpoint
+        .checkpoint_contents
+        .iter()
+        .next()
+        .unwrap()
+        .transaction;
+    let bundle = ProofBundle {
+        transaction,
+        end_of_epoch_checkpoints: vec![],
+        full_checkpoint,
+    };
+
+    let bundle = ProofBundle::from_bytes(&bundle.to_bytes().unwrap()).unwrap();
+    let genesis = genesis_committee(&config).unwrap();
+    bundle.verify(&genesis).unwrap();
+
+    // A chain that does not start at genesis is rejected
+    let mut broken = bundle.clone();
+    broken.end_of_epoch_checkpoints = vec![client.read_checkpoint(176295).unwrap()];
+    assert!(broken.verify(&genesis).is_err());
+}
@@ -0,0 +1,21 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use sui_light_client::decode_checkpoint_blob;
+
+#[test]
+fn unsupported_blob_version_is_rejected() {
+    let err = decode_checkpoint_blob(&[2, 0, 0, 0]).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported checkpoint blob version 2");
+}
+
+#[test]
+fn empty_blob_is_rejected() {
+    assert!(decode_checkpoint_blob(&[]).is_err());
+}
+
+#[test]
+fn truncated_blob_is_rejected() {
+    let err = decode_checkpoint_blob(&[1, 0]).unwrap_err();
+    assert!(!err.to_string().contains("unsupported"));
+}
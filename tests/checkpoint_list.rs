@@ -0,0 +1,130 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::{fs, sync::Arc};
+
+use sui_light_client::{check_contiguous_epochs, CheckpointsList, LightClient, SyncState};
+use sui_types::messages_checkpoint::CertifiedCheckpointSummary;
+
+mod common;
+
+use common::{empty_checkpoint_dir, example_config, StubNetwork, TestChain};
+
+fn read_checkpoints(ckp_ids: &[u64]) -> Vec<CertifiedCheckpointSummary> {
+    let client = LightClient::new(example_config()).unwrap();
+    ckp_ids
+        .iter()
+        .map(|ckp_id| client.read_checkpoint(*ckp_id).unwrap())
+        .collect()
+}
+
+#[test]
+fn consecutive_epochs_pass() {
+    // The last checkpoints of epochs 1, 2 and 3
+    let summaries = read_checkpoints(&[90645, 176295, 256378]);
+    check_contiguous_epochs(&summaries, 1).unwrap();
+}
+
+#[test]
+fn missing_epoch_is_reported() {
+    let summaries = read_checkpoints(&[90645, 256378]);
+    let err = check_contiguous_epochs(&summaries, 1).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("gap between epoch 1 and 3"));
+    assert!(message.contains("90645") && message.contains("256378"));
+}
+
+#[test]
+fn wrong_first_epoch_is_reported() {
+    let summaries = read_checkpoints(&[176295]);
+    let err = check_contiguous_epochs(&summaries, 1).unwrap_err();
+    assert!(err.to_string().contains("expected epoch 1"));
+}
+
+#[test]
+fn interrupted_list_write_keeps_the_previous_list() {
+    let dir = empty_checkpoint_dir("list-write");
+    let mut config = example_config();
+    config.checkpoint_summary_dir = dir.clone();
+    let client = LightClient::new(config).unwrap();
+
+    let mut list = CheckpointsList {
+        checkpoints: vec![90645, 176295],
+    };
+    client.write_checkpoint_list(&list).unwrap();
+
+    // A write interrupted halfway only leaves a truncated temporary file behind
+    fs::write(dir.join("checkpoints.yaml.tmp"), "checkpoints:\n  - 9064").unwrap();
+    assert_eq!(
+        client.read_checkpoint_list().unwrap().checkpoints,
+        list.checkpoints
+    );
+
+    list.checkpoints.push(256378);
+    client.write_checkpoint_list(&list).unwrap();
+    assert_eq!(
+        client.read_checkpoint_list().unwrap().checkpoints,
+        list.checkpoints
+    );
+    assert!(!dir.join("checkpoints.yaml.tmp").exists());
+}
+
+#[test]
+fn list_is_current_until_an_epoch_ends() {
+    let list = CheckpointsList {
+        checkpoints: vec![90645, 176295],
+    };
+    let state = SyncState {
+        last_listed_checkpoint: Some(176295),
+        next_listed_epoch: Some(3),
+        ..Default::default()
+    };
+    assert!(state.is_list_current(&list, 3));
+    assert!(!state.is_list_current(&list, 4));
+
+    // A list changed since, e.g. by init, is synced again
+    let other = CheckpointsList {
+        checkpoints: vec![90645],
+    };
+    assert!(!state.is_list_current(&other, 3));
+    assert!(!SyncState::default().is_list_current(&list, 3));
+}
+
+#[tokio::test]
+async fn no_op_sync_matches_a_fresh_sync() {
+    // Epochs 0 to 2 end at checkpoints 1, 3 and 5, the latest checkpoint is in epoch 2 at first
+    let chain = TestChain::new(&[2, 2, 2, 2]);
+    let stub = Arc::new(StubNetwork::new(&chain));
+    stub.set_latest_checkpoint(4);
+    let client = chain.client("incremental-sync", stub.clone()).await;
+
+    client.sync_checkpoint_list_to_latest().await.unwrap();
+    let list = client.read_checkpoint_list().unwrap();
+    assert_eq!(list.checkpoints, vec![1, 3]);
+    let state = client.read_sync_state().unwrap();
+    assert_eq!(state.last_listed_checkpoint, Some(3));
+    assert_eq!(state.next_listed_epoch, Some(2));
+
+    // A second sync short-circuits while no epoch ended
+    client.sync_checkpoint_list_to_latest().await.unwrap();
+    assert_eq!(
+        client.read_checkpoint_list().unwrap().checkpoints,
+        list.checkpoints
+    );
+
+    // and picks up from the recorded frontier once one did, agreeing with a fresh sync
+    stub.set_latest_checkpoint(7);
+    client.sync_checkpoint_list_to_latest().await.unwrap();
+    let resynced = client.read_checkpoint_list().unwrap();
+    assert_eq!(resynced.checkpoints, vec![1, 3, 5]);
+    let state = client.read_sync_state().unwrap();
+    assert_eq!(state.last_listed_checkpoint, Some(5));
+    assert_eq!(state.next_listed_epoch, Some(3));
+
+    let fresh = chain.client("fresh-sync", stub).await;
+    fresh.sync_checkpoint_list_to_latest().await.unwrap();
+    assert_eq!(
+        fresh.read_checkpoint_list().unwrap().checkpoints,
+        resynced.checkpoints
+    );
+}
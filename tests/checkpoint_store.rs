@@ -0,0 +1,76 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::fs;
+
+use sui_light_client::LightClient;
+
+mod common;
+
+use common::{empty_checkpoint_dir, example_config};
+
+#[test]
+fn written_checkpoints_are_read_back_after_reopening() {
+    let summary = LightClient::new(example_config())
+        .unwrap()
+        .read_checkpoint(90645)
+        .unwrap();
+
+    let mut config = example_config();
+    config.checkpoint_summary_dir = empty_checkpoint_dir("store");
+    let client = LightClient::new(config.clone()).unwrap();
+    client.write_checkpoint(&summary).unwrap();
+    client.write_checkpoint(&summary).unwrap();
+
+    let client = LightClient::new(config).unwrap();
+    assert_eq!(client.stored_checkpoints(), vec![90645]);
+    assert_eq!(
+        client.read_checkpoint(90645).unwrap().digest(),
+        summary.digest()
+    );
+    assert!(client.read_checkpoint(90646).is_err());
+}
+
+#[test]
+fn legacy_checkpoint_files_are_migrated() {
+    let mut config = example_config();
+    let dir = empty_checkpoint_dir("migrate");
+    for file in ["90645.yaml", "checkpoints.yaml"] {
+        fs::copy(config.checkpoint_summary_dir.join(file), dir.join(file)).unwrap();
+    }
+    config.checkpoint_summary_dir = dir;
+
+    let client = LightClient::new(config).unwrap();
+    assert!(client.has_checkpoint(90645));
+    assert!(client.stored_checkpoints().is_empty());
+
+    assert_eq!(client.migrate_legacy_checkpoints().unwrap(), 1);
+    assert_eq!(client.migrate_legacy_checkpoints().unwrap(), 0);
+    assert_eq!(client.stored_checkpoints(), vec![90645]);
+}
+
+#[test]
+fn ordinary_checkpoints_are_stored_apart_from_end_of_epoch_ones() {
+    let end_of_epoch = LightClient::new(example_config())
+        .unwrap()
+        .read_checkpoint(90645)
+        .unwrap();
+    let mut ordinary = end_of_epoch.clone();
+    ordinary.data_mut_for_testing().sequence_number = 90644;
+    ordinary.data_mut_for_testing().end_of_epoch_data = None;
+
+    let mut config = example_config();
+    config.checkpoint_summary_dir = empty_checkpoint_dir("ordinary");
+    let client = LightClient::new(config.clone()).unwrap();
+    client.write_checkpoint(&ordinary).unwrap();
+    client.write_checkpoint(&end_of_epoch).unwrap();
+
+    let client = LightClient::new(config).unwrap();
+    assert_eq!(client.stored_checkpoints(), vec![90644, 90645]);
+    assert_eq!(client.stored_end_of_epoch_checkpoints(), vec![90645]);
+    assert!(client
+        .read_checkpoint(90644)
+        .unwrap()
+        .end_of_epoch_data
+        .is_none());
+}
@@ -0,0 +1,95 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use sui_light_client::{CheckpointSource, CircuitState, LightClient, LightClientError};
+
+mod common;
+
+use common::example_config;
+
+#[tokio::test]
+#[ignore = "connects to the testnet and dWallet full nodes"]
+async fn rpc_clients_are_built_once() {
+    let config = example_config();
+    let client = LightClient::new(config).unwrap();
+    assert_eq!(client.rpc_clients_built(), 0);
+
+    // Concurrent and repeated requests share the connected clients
+    for _ in 0..3 {
+        let (sui, dwallet) = tokio::join!(client.sui_client(), client.dwallet_client());
+        sui.unwrap()
+            .read_api()
+            .get_latest_checkpoint_sequence_number()
+            .await
+            .unwrap();
+        dwallet
+            .unwrap()
+            .governance_api()
+            .get_reference_gas_price()
+            .await
+            .unwrap();
+    }
+    assert_eq!(client.rpc_clients_built(), 2);
+}
+
+#[tokio::test]
+async fn dwallet_breaker_opens_after_consecutive_failures() {
+    let mut config = example_config();
+    // Nothing listens on the discard port, so every connection is refused
+    config.dwallet_full_node_url = "http://127.0.0.1:9".to_string();
+    config.dwallet_breaker_threshold = 2;
+    config.dwallet_retry_budget_secs = 60;
+    let client = LightClient::new(config).unwrap();
+
+    let err = client
+        .dwallet_request("Querying the dWallet gas price", |dwallet_client| {
+            dwallet_client.read_api().get_reference_gas_price()
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LightClientError>(),
+        Some(LightClientError::DwalletUnavailable { failures: 2 })
+    ));
+    assert_eq!(
+        client.dwallet_circuit_state(),
+        CircuitState::Open {
+            consecutive_failures: 2
+        }
+    );
+
+    // The open breaker fails without connecting again
+    let err = client
+        .dwallet_request("Querying the dWallet gas price", |dwallet_client| {
+            dwallet_client.read_api().get_reference_gas_price()
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LightClientError>(),
+        Some(LightClientError::DwalletUnavailable { failures: 2 })
+    ));
+    assert_eq!(client.rpc_clients_built(), 2);
+}
+
+#[tokio::test]
+#[ignore = "downloads the latest checkpoint from the testnet full node REST API"]
+async fn checkpoints_are_downloaded_from_the_full_node() {
+    let mut config = example_config();
+    config.checkpoint_source = CheckpointSource::FullNode;
+    config.object_store_url = String::new();
+    let client = LightClient::new(config).unwrap();
+
+    let seq = client
+        .sui_client()
+        .await
+        .unwrap()
+        .read_api()
+        .get_latest_checkpoint_sequence_number()
+        .await
+        .unwrap();
+    let checkpoint = client.get_full_checkpoint(seq).await.unwrap();
+    assert_eq!(checkpoint.checkpoint_summary.sequence_number, seq);
+    let summary = client.download_checkpoint_summary(seq).await.unwrap();
+    assert_eq!(summary.digest(), checkpoint.checkpoint_summary.digest());
+}
@@ -0,0 +1,353 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+//! Fixtures shared by the integration tests, each of which uses only some of them.
+#![allow(dead_code)]
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use object_store::{memory::InMemory, path::Path, ObjectStore};
+use sui_light_client::{
+    committee_from_stakes, Config, EpochLookup, LightClient, LightClientError, NetworkStub,
+    TransactionProof,
+};
+use sui_protocol_config::ProtocolVersion;
+use sui_rest_api::CheckpointData;
+use sui_types::{
+    base_types::{random_object_ref, AuthorityName, ObjectID, ObjectRef, SuiAddress},
+    committee::Committee,
+    crypto::{
+        get_authority_key_pair, get_key_pair, AccountKeyPair, AuthorityKeyPair, KeypairTraits,
+    },
+    digests::TransactionDigest,
+    effects::TestEffectsBuilder,
+    full_checkpoint_content::CheckpointTransaction,
+    gas::GasCostSummary,
+    messages_checkpoint::{
+        CertifiedCheckpointSummary, CheckpointContents, CheckpointSummary, EndOfEpochData,
+        SignedCheckpointSummary,
+    },
+    transaction::TransactionData,
+    utils::to_sender_signed_transaction,
+};
+
+/// The example testnet config, with the checkpoints stored in `example_config/`.
+pub fn example_config() -> Config {
+    let reader = fs::File::open("example_config/light_client.yaml").unwrap();
+    serde_yaml::from_reader(reader).unwrap()
+}
+
+/// An empty checkpoint directory unique to test `name` and this test run, so parallel
+/// tests and concurrent runs never share one.
+pub fn empty_checkpoint_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("sui-light-client-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Checkpoints hand-signed by a committee of 4 validators with equal stakes that changes
+/// every epoch, each with two transfer transactions.
+pub struct TestChain {
+    /// The validator keys of each epoch, sorted by name
+    keys: Vec<Vec<(AuthorityName, AuthorityKeyPair)>>,
+    /// The committee of each epoch, from genesis
+    pub committees: Vec<Committee>,
+    /// Every checkpoint, by sequence number
+    pub checkpoints: Vec<CheckpointData>,
+}
+
+impl TestChain {
+    /// A chain whose epoch `i` has `epoch_lengths[i]` checkpoints, the last of which ends
+    /// it, except in the last epoch.
+    pub fn new(epoch_lengths: &[u64]) -> Self {
+        let keys: Vec<_> = epoch_lengths.iter().map(|_| validator_keys(4)).collect();
+        let committees: Vec<_> = keys
+            .iter()
+            .enumerate()
+            .map(|(epoch, keys)| {
+                committee_from_stakes(epoch as u64, keys.iter().map(|(name, _)| (*name, 2500)))
+                    .unwrap()
+            })
+            .collect();
+
+        let mut chain = Self {
+            keys,
+            committees,
+            checkpoints: vec![],
+        };
+        for (epoch, length) in epoch_lengths.iter().enumerate() {
+            for i in 0..*length {
+                let end_of_epoch_data =
+                    (i + 1 == *length && epoch + 1 < epoch_lengths.len()).then(|| EndOfEpochData {
+                        next_epoch_committee: chain.committees[epoch + 1].voting_rights.clone(),
+                        next_epoch_protocol_version: ProtocolVersion::new(epoch as u64 + 2),
+                        epoch_commitments: vec![],
+                    });
+                let checkpoint = chain.checkpoint(epoch as u64, end_of_epoch_data);
+                chain.checkpoints.push(checkpoint);
+            }
+        }
+        chain
+    }
+
+    /// The next checkpoint of `epoch`, with two transfer transactions
+    fn checkpoint(&self, epoch: u64, end_of_epoch_data: Option<EndOfEpochData>) -> CheckpointData {
+        let seq = self.checkpoints.len() as u64;
+        let transactions: Vec<_> = (0..2).map(|_| transfer_transaction(seq + 1)).collect();
+        let contents = CheckpointContents::new_with_digests_only_for_tests(
+            transactions.iter().map(|tx| tx.effects.execution_digests()),
+        );
+
+        let summary = CheckpointSummary {
+            epoch,
+            sequence_number: seq,
+            network_total_transactions: 2 * (seq + 1),
+            content_digest: *contents.digest(),
+            previous_digest: self
+                .checkpoints
+                .last()
+                .map(|previous| *previous.checkpoint_summary.digest()),
+            epoch_rolling_gas_cost_summary: GasCostSummary::default(),
+            timestamp_ms: seq * 1000,
+            checkpoint_commitments: vec![],
+            end_of_epoch_data,
+            version_specific_data: vec![],
+        };
+        CheckpointData {
+            checkpoint_summary: self.sign(summary),
+            checkpoint_contents: contents,
+            transactions,
+        }
+    }
+
+    /// Certifies `summary` with the signatures of every validator of its epoch.
+    pub fn sign(&self, summary: CheckpointSummary) -> CertifiedCheckpointSummary {
+        let epoch = summary.epoch;
+        let signatures = self.keys[epoch as usize]
+            .iter()
+            .map(|(name, key)| {
+                SignedCheckpointSummary::new(epoch, summary.clone(), key, *name)
+                    .auth_sig()
+                    .clone()
+            })
+            .collect();
+        CertifiedCheckpointSummary::new(summary, signatures, &self.committees[epoch as usize])
+            .unwrap()
+    }
+
+    /// The sequence numbers of the end-of-epoch checkpoints
+    pub fn end_of_epoch_checkpoints(&self) -> Vec<u64> {
+        self.checkpoints
+            .iter()
+            .map(|checkpoint| &checkpoint.checkpoint_summary)
+            .filter(|summary| summary.end_of_epoch_data.is_some())
+            .map(|summary| summary.sequence_number)
+            .collect()
+    }
+
+    /// The digest of the first transaction of checkpoint `seq`
+    pub fn transaction(&self, seq: u64) -> TransactionDigest {
+        *self.checkpoints[seq as usize].transactions[0]
+            .transaction
+            .digest()
+    }
+
+    /// An in-memory object store holding every checkpoint at the paths of `config`.
+    pub async fn object_store(&self, config: &Config) -> Box<dyn ObjectStore> {
+        let store = InMemory::new();
+        for checkpoint in &self.checkpoints {
+            let seq = checkpoint.checkpoint_summary.sequence_number;
+            let path = Path::from(config.checkpoint_object_path(seq, None).unwrap());
+            let mut blob = vec![1];
+            blob.extend(bcs::to_bytes(checkpoint).unwrap());
+            store.put(&path, blob.into()).await.unwrap();
+        }
+        Box::new(store)
+    }
+
+    /// A client of this chain with an empty checkpoint directory unique to test `name`,
+    /// trusting its genesis committee and finding end-of-epoch checkpoints by binary
+    /// search, whose full node and dWallet network are `stub`.
+    pub async fn client(&self, name: &str, stub: Arc<StubNetwork>) -> LightClient {
+        let mut config = example_config();
+        config.checkpoint_summary_dir = empty_checkpoint_dir(name);
+        config.epoch_lookup = EpochLookup::BinarySearch;
+        let store = self.object_store(&config).await;
+        LightClient::with_object_store(config, store)
+            .unwrap()
+            .with_genesis_committee(self.committees[0].clone())
+            .with_network_stub(stub)
+    }
+}
+
+/// A transfer of `amount` MIST between new accounts, with the effects of a successful
+/// execution.
+pub fn transfer_transaction(amount: u64) -> CheckpointTransaction {
+    let (sender, sender_key) = get_key_pair::<AccountKeyPair>();
+    let data = TransactionData::new_transfer_sui(
+        SuiAddress::random_for_testing_only(),
+        sender,
+        Some(amount),
+        random_object_ref(),
+        1_000_000,
+        1000,
+    );
+    let transaction = to_sender_signed_transaction(data, &sender_key);
+    let effects = TestEffectsBuilder::new(transaction.data()).build();
+    CheckpointTransaction {
+        transaction,
+        effects,
+        events: None,
+        input_objects: vec![],
+        output_objects: vec![],
+    }
+}
+
+/// `count` validator keys, sorted by name.
+pub fn validator_keys(count: usize) -> Vec<(AuthorityName, AuthorityKeyPair)> {
+    let mut keys: Vec<(AuthorityName, AuthorityKeyPair)> = (0..count)
+        .map(|_| {
+            let (_, key) = get_authority_key_pair();
+            (key.public().into(), key)
+        })
+        .collect();
+    keys.sort_by_key(|(name, _)| *name);
+    keys
+}
+
+/// The full node and dWallet network of a [`TestChain`], whose dWallet registry starts
+/// with the genesis committee and records the committees and proofs submitted to it.
+pub struct StubNetwork {
+    latest_checkpoint: AtomicU64,
+    transactions: HashMap<TransactionDigest, u64>,
+    /// The committee object registered for each epoch
+    pub registered: Mutex<BTreeMap<u64, ObjectRef>>,
+    /// The transactions whose proof was submitted, in order
+    pub proofs: Mutex<Vec<TransactionDigest>>,
+}
+
+impl StubNetwork {
+    pub fn new(chain: &TestChain) -> Self {
+        let transactions = chain
+            .checkpoints
+            .iter()
+            .flat_map(|checkpoint| {
+                let seq = checkpoint.checkpoint_summary.sequence_number;
+                checkpoint
+                    .transactions
+                    .iter()
+                    .map(move |tx| (*tx.transaction.digest(), seq))
+            })
+            .collect();
+        Self {
+            latest_checkpoint: AtomicU64::new(chain.checkpoints.len() as u64 - 1),
+            transactions,
+            registered: Mutex::new(BTreeMap::from([(0, random_object_ref())])),
+            proofs: Mutex::new(vec![]),
+        }
+    }
+
+    /// Reports checkpoint `seq` as the latest, the last of the chain at first.
+    pub fn set_latest_checkpoint(&self, seq: u64) {
+        self.latest_checkpoint.store(seq, Ordering::SeqCst);
+    }
+
+    /// The epochs whose committee is registered
+    pub fn registered_epochs(&self) -> Vec<u64> {
+        self.registered.lock().unwrap().keys().copied().collect()
+    }
+}
+
+#[async_trait]
+impl NetworkStub for StubNetwork {
+    async fn latest_checkpoint(&self) -> anyhow::Result<u64> {
+        Ok(self.latest_checkpoint.load(Ordering::SeqCst))
+    }
+
+    async fn transaction_checkpoint(&self, tid: TransactionDigest) -> anyhow::Result<u64> {
+        self.transactions
+            .get(&tid)
+            .copied()
+            .ok_or_else(|| LightClientError::TransactionNotFound(tid).into())
+    }
+
+    async fn highest_registered_epoch(&self) -> anyhow::Result<u64> {
+        Ok(self.registered_epochs().last().copied().unwrap_or(0))
+    }
+
+    async fn epoch_committee_id(&self, epoch: u64) -> anyhow::Result<ObjectID> {
+        self.registered
+            .lock()
+            .unwrap()
+            .get(&epoch)
+            .map(|object_ref| object_ref.0)
+            .ok_or_else(|| LightClientError::EpochCommitteeNotRegistered { epoch }.into())
+    }
+
+    async fn submit_committee(
+        &self,
+        summary: &CertifiedCheckpointSummary,
+        prev_committee_object_ref: Option<ObjectRef>,
+        dry_run: bool,
+    ) -> anyhow::Result<Option<ObjectRef>> {
+        let epoch = summary.epoch();
+        let mut registered = self.registered.lock().unwrap();
+        anyhow::ensure!(
+            !registered.contains_key(&epoch),
+            "Committee of epoch {} is already registered",
+            epoch
+        );
+        let prev = registered
+            .get(&(epoch - 1))
+            .copied()
+            .ok_or_else(|| anyhow!("Committee of epoch {} is not registered", epoch - 1))?;
+        if let Some(given) = prev_committee_object_ref {
+            anyhow::ensure!(
+                given == prev,
+                "Stale committee object of epoch {}",
+                epoch - 1
+            );
+        }
+        if dry_run {
+            return Ok(None);
+        }
+        let object_ref = random_object_ref();
+        registered.insert(epoch, object_ref);
+        Ok(Some(object_ref))
+    }
+
+    async fn submit_proofs(
+        &self,
+        proofs: &[(TransactionProof, ObjectID)],
+        dry_run: bool,
+    ) -> anyhow::Result<Option<TransactionDigest>> {
+        for (proof, committee_id) in proofs {
+            let epoch = proof.checkpoint_summary.epoch();
+            anyhow::ensure!(
+                self.epoch_committee_id(epoch).await? == *committee_id,
+                "Proof of {} is checked against the wrong committee",
+                proof.transaction_digest()
+            );
+        }
+        if dry_run {
+            return Ok(None);
+        }
+        self.proofs
+            .lock()
+            .unwrap()
+            .extend(proofs.iter().map(|(proof, _)| *proof.transaction_digest()));
+        Ok(Some(TransactionDigest::random()))
+    }
+}
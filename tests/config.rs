@@ -0,0 +1,205 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::path::PathBuf;
+
+use sui_light_client::{CheckpointSource, Config, EpochLookup, LightClient, CONFIG_VERSION};
+
+mod common;
+
+use common::example_config;
+
+#[test]
+fn example_config_is_valid() {
+    example_config().validate().unwrap();
+}
+
+#[test]
+fn every_invalid_field_is_reported() {
+    let mut config = example_config();
+    config.graphql_url = "not a url".to_string();
+    config.dwltn_registry_object_id = "0xnothex".to_string();
+    config.dwltn_config_object_id = "0x12zz".to_string();
+
+    let message = config.validate().unwrap_err().to_string();
+    assert!(message.starts_with("3 invalid config field(s)"));
+    for field in [
+        "graphql_url",
+        "dwltn_registry_object_id",
+        "dwltn_config_object_id",
+    ] {
+        assert!(message.contains(field), "{} not reported", field);
+    }
+}
+
+#[test]
+fn dwallet_object_ids_are_left_for_init_to_set() {
+    let mut config = example_config();
+    config.dwltn_registry_object_id = String::new();
+    config.dwltn_config_object_id = String::new();
+    config.validate().unwrap();
+
+    let err = config.registry_object_id().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "dwltn_registry_object_id is not set, run init first"
+    );
+    assert!(config.state_proof_config_object_id().is_err());
+}
+
+#[test]
+fn empty_urls_are_accepted_with_a_network_preset() {
+    let mut config = example_config();
+    config.sui_full_node_url = String::new();
+    assert!(config.validate().is_err());
+
+    config.network = Some(sui_light_client::Network::Testnet);
+    config.validate().unwrap();
+}
+
+#[test]
+fn genesis_file_defaults_to_the_one_of_the_network_preset() {
+    let mut config = example_config();
+    config.genesis_filename = PathBuf::new();
+    assert!(config.validate().is_err());
+
+    config.network = Some(sui_light_client::Network::Mainnet);
+    config.validate().unwrap();
+    config.apply_network_defaults().unwrap();
+    assert_eq!(
+        config.genesis_filename,
+        PathBuf::from("genesis_mainnet.blob")
+    );
+}
+
+#[test]
+fn missing_keystore_path_is_reported() {
+    let mut config = example_config();
+    config.keystore_path = Some("example_config/missing.keystore".into());
+
+    let message = config.validate().unwrap_err().to_string();
+    assert!(message.contains("keystore_path"));
+    assert!(config.keystore().is_err());
+}
+
+#[test]
+fn graphql_url_is_optional_with_binary_search() {
+    let mut config = example_config();
+    config.graphql_url = String::new();
+    assert!(config.validate().is_err());
+
+    config.epoch_lookup = EpochLookup::BinarySearch;
+    config.validate().unwrap();
+    config.apply_network_defaults().unwrap();
+}
+
+#[test]
+fn object_store_url_is_optional_with_full_node_checkpoints() {
+    let mut config = example_config();
+    config.object_store_url = String::new();
+    assert!(config.validate().is_err());
+
+    config.checkpoint_source = CheckpointSource::FullNode;
+    config.validate().unwrap();
+    config.apply_network_defaults().unwrap();
+    assert_eq!(config.object_store_url, "");
+}
+
+#[test]
+fn object_store_url_scheme_is_checked() {
+    let mut config = example_config();
+    for url in [
+        "s3://checkpoints",
+        "gs://checkpoints",
+        "file:///tmp/checkpoints",
+    ] {
+        config.object_store_url = url.to_string();
+        config.validate().unwrap();
+    }
+    for url in ["ftp://checkpoints", "checkpoints.testnet.sui.io"] {
+        config.object_store_url = url.to_string();
+        let message = config.validate().unwrap_err().to_string();
+        assert!(
+            message.contains(&format!(
+                "object_store_url must be one of https://, s3://, gs://, file://; got '{}'",
+                url
+            )),
+            "{}",
+            message
+        );
+        assert!(LightClient::new(config.clone()).is_err());
+    }
+}
+
+#[test]
+fn object_store_path_template_is_substituted() {
+    let mut config = example_config();
+    assert_eq!(
+        config.checkpoint_object_path(90645, None).unwrap(),
+        "90645.chk"
+    );
+
+    config.object_store_path_template = "epoch={epoch}/{seq:10}.chk".to_string();
+    assert_eq!(
+        config.checkpoint_object_path(90645, Some(1)).unwrap(),
+        "epoch=1/0000090645.chk"
+    );
+    assert!(config.checkpoint_object_path(90645, None).is_err());
+
+    config.object_store_path_template = "{sequence}.chk".to_string();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn object_store_limits_must_be_positive() {
+    let mut config = example_config();
+    config.object_store_max_concurrency = 0;
+    config.object_store_rps = Some(0);
+
+    let message = config.validate().unwrap_err().to_string();
+    assert!(message.contains("object_store_max_concurrency"));
+    assert!(message.contains("object_store_rps"));
+}
+
+#[test]
+fn unversioned_config_is_upgraded_with_defaults() {
+    let yaml = r#"
+sui_full_node_url: "https://fullnode.testnet.sui.io:443"
+dwallet_full_node_url: "https://fullnode.alpha.testnet.dwallet.cloud:443"
+checkpoint_summary_dir: example_config/
+genesis_filename: genesis_testnet.blob
+"#;
+    let (config, added) = Config::migrate(serde_yaml::from_str(yaml).unwrap()).unwrap();
+    assert_eq!(config.config_version, CONFIG_VERSION);
+    assert_eq!(config.dwltn_config_object_id, "");
+    assert_eq!(config.request_timeout_secs, 30);
+
+    let added = added.unwrap();
+    for field in [
+        "config_version",
+        "dwltn_registry_object_id",
+        "request_timeout_secs",
+    ] {
+        assert!(
+            added.iter().any(|added| added == field),
+            "{} not added",
+            field
+        );
+    }
+    assert!(!added.iter().any(|added| added == "genesis_filename"));
+
+    // The upgraded config is up to date
+    let upgraded = serde_yaml::to_value(&config).unwrap();
+    assert!(Config::migrate(upgraded).unwrap().1.is_none());
+}
+
+#[test]
+fn newer_config_version_is_rejected() {
+    let mut value = serde_yaml::to_value(example_config()).unwrap();
+    value.as_mapping_mut().unwrap().insert(
+        "config_version".into(),
+        serde_yaml::to_value(CONFIG_VERSION + 1).unwrap(),
+    );
+    let message = Config::migrate(value).unwrap_err().to_string();
+    assert!(message.contains("newer"));
+}
@@ -0,0 +1,141 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::str::FromStr;
+
+use sui_light_client::{
+    diff_committees, genesis_committee, verify_end_of_epoch_summary, LightClient, LightClientError,
+    Network,
+};
+use sui_types::digests::CheckpointDigest;
+
+mod common;
+
+use common::example_config;
+
+#[test]
+fn genesis_committee_is_for_epoch_zero() {
+    let committee = genesis_committee(&example_config()).unwrap();
+    assert_eq!(committee.epoch, 0);
+    assert!(!committee.voting_rights.is_empty());
+}
+
+#[test]
+fn genesis_validators_have_network_addresses() {
+    let config = example_config();
+    let client = LightClient::new(config.clone()).unwrap();
+    let addresses = client.genesis_validator_addresses().unwrap();
+
+    let committee = genesis_committee(&config).unwrap();
+    assert_eq!(addresses.len(), committee.num_members());
+    for (name, _) in committee.members() {
+        assert!(!addresses[name].is_empty());
+    }
+}
+
+#[test]
+fn genesis_committee_epoch_is_configurable() {
+    let mut config = example_config();
+    config.genesis_committee_epoch = 3;
+    let committee = genesis_committee(&config).unwrap();
+    assert_eq!(committee.epoch, 3);
+    assert_eq!(
+        committee.voting_rights,
+        genesis_committee(&example_config()).unwrap().voting_rights
+    );
+}
+
+#[test]
+fn genesis_committee_does_not_sign_later_epochs() {
+    let config = example_config();
+    let client = LightClient::new(config.clone()).unwrap();
+
+    // The first stored checkpoint closes epoch 1, which is signed by the committee
+    // handed over at the end of epoch 0 rather than by the genesis committee
+    let summary = client.read_checkpoint(90645).unwrap();
+    assert_eq!(summary.epoch(), 1);
+    let committee = genesis_committee(&config).unwrap();
+    let err = verify_end_of_epoch_summary(&summary, &committee).unwrap_err();
+    let LightClientError::InvalidSignature { diagnostic, .. } = err else {
+        panic!("expected an invalid signature, got {}", err);
+    };
+    assert_eq!(diagnostic.signature_epoch, 1);
+    assert_eq!(diagnostic.committee_epoch, 0);
+    assert!(diagnostic.cause().contains("sync the committee of epoch 1"));
+}
+
+#[tokio::test]
+#[ignore = "downloads the last checkpoint of epoch 0 from the testnet object store"]
+async fn genesis_committee_verifies_last_checkpoint_of_epoch_zero() {
+    let config = example_config();
+    let client = LightClient::new(config.clone()).unwrap();
+
+    let seq = client.query_last_checkpoint_of_epoch(0).await.unwrap();
+    let summary = client.download_checkpoint_summary(seq).await.unwrap();
+    let committee = genesis_committee(&config).unwrap();
+    let next_committee = verify_end_of_epoch_summary(&summary, &committee).unwrap();
+    assert_eq!(next_committee.epoch, 1);
+}
+
+#[tokio::test]
+#[ignore = "queries the committees of epochs 0 and 1 from the testnet full node"]
+async fn derived_committees_match_the_full_node() {
+    let config = example_config();
+    let client = LightClient::new(config.clone()).unwrap();
+
+    let committee = genesis_committee(&config).unwrap();
+    let onchain = client.fetch_onchain_committee(0).await.unwrap();
+    assert_eq!(diff_committees(&committee, &onchain), vec![]);
+
+    // The committee handed over by the end-of-epoch data of the last checkpoint of epoch 0
+    let seq = client.query_last_checkpoint_of_epoch(0).await.unwrap();
+    let summary = client.download_checkpoint_summary(seq).await.unwrap();
+    let next_committee = verify_end_of_epoch_summary(&summary, &committee).unwrap();
+    let onchain = client.fetch_onchain_committee(1).await.unwrap();
+    assert_eq!(onchain.epoch, 1);
+    assert_eq!(diff_committees(&next_committee, &onchain), vec![]);
+}
+
+#[tokio::test]
+async fn genesis_digest_must_match_the_genesis_file() {
+    let mut config = example_config();
+    config.offline = true;
+    config.genesis_digest = Some(CheckpointDigest::new([0; 32]));
+    let err = LightClient::new(config.clone())
+        .unwrap()
+        .check_genesis()
+        .await
+        .unwrap_err();
+    let Some(LightClientError::GenesisMismatch { genesis, .. }) = err.downcast_ref() else {
+        panic!("expected a genesis mismatch, got {:?}", err);
+    };
+    assert!(err.to_string().contains("wrong network?"));
+
+    // The digest of the genesis file itself passes
+    config.genesis_digest = Some(CheckpointDigest::from_str(genesis).unwrap());
+    let client = LightClient::new(config).unwrap();
+    client.check_genesis().await.unwrap();
+}
+
+#[tokio::test]
+async fn network_preset_rejects_the_genesis_of_another_network() {
+    let mut config = example_config();
+    config.offline = true;
+    config.network = Some(Network::Mainnet);
+    let err = LightClient::new(config.clone())
+        .unwrap()
+        .check_genesis()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(LightClientError::GenesisMismatch { .. })
+    ));
+
+    config.network = Some(Network::Testnet);
+    LightClient::new(config)
+        .unwrap()
+        .check_genesis()
+        .await
+        .unwrap();
+}
@@ -0,0 +1,374 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::{
+    fmt, fs,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt};
+use object_store::{
+    memory::InMemory, path::Path, GetOptions, GetRange, GetResult, GetResultPayload, ListResult,
+    MultipartUpload, ObjectMeta, ObjectStore, PutMultipartOpts, PutOptions, PutPayload, PutResult,
+};
+use sui_light_client::{Config, LightClient, LightClientError, SyncOptions};
+use sui_rest_api::CheckpointData;
+use sui_types::{effects::TransactionEffectsAPI, messages_checkpoint::CheckpointContents};
+
+mod common;
+
+use common::{example_config, StubNetwork, TestChain};
+
+/// An in-memory object store holding the stored example checkpoints at the paths of the
+/// `config` template, as blobs without transactions.
+async fn recorded_object_store(config: &Config, seqs: &[u64]) -> Box<dyn ObjectStore> {
+    let store = InMemory::new();
+    for seq in seqs {
+        let path = Path::from(config.checkpoint_object_path(*seq, None).unwrap());
+        store
+            .put(&path, checkpoint_blob(config, *seq).into())
+            .await
+            .unwrap();
+    }
+    Box::new(store)
+}
+
+/// The blob of stored example checkpoint `seq`, without transactions.
+fn checkpoint_blob(config: &Config, seq: u64) -> Vec<u8> {
+    let client = LightClient::new(config.clone()).unwrap();
+    let checkpoint = CheckpointData {
+        checkpoint_summary: client.read_checkpoint(seq).unwrap(),
+        checkpoint_contents: CheckpointContents::new_with_digests_only_for_tests(vec![]),
+        transactions: vec![],
+    };
+    let mut blob = vec![1];
+    blob.extend(bcs::to_bytes(&checkpoint).unwrap());
+    blob
+}
+
+#[tokio::test]
+async fn checkpoints_are_downloaded_from_the_object_store() {
+    let config = example_config();
+    let store = recorded_object_store(&config, &[90645, 176295]).await;
+    let client = LightClient::with_object_store(config, store).unwrap();
+
+    for seq in [90645, 176295] {
+        let summary = client.download_checkpoint_summary(seq).await.unwrap();
+        assert_eq!(
+            summary.digest(),
+            client.read_checkpoint(seq).unwrap().digest()
+        );
+    }
+    let full_checkpoint = client.get_full_checkpoint(90645).await.unwrap();
+    assert!(full_checkpoint.transactions.is_empty());
+}
+
+#[tokio::test]
+async fn missing_checkpoint_is_not_found() {
+    let config = example_config();
+    let store = recorded_object_store(&config, &[90645]).await;
+    let client = LightClient::with_object_store(config, store).unwrap();
+
+    let err = client
+        .download_checkpoint_summary(176295)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(LightClientError::CheckpointNotFound(176295))
+    ));
+}
+
+#[tokio::test]
+async fn missing_checkpoints_are_read_from_the_fallback_stores() {
+    let archive_dir =
+        std::env::temp_dir().join(format!("light-client-archive-{}", std::process::id()));
+    fs::create_dir_all(&archive_dir).unwrap();
+    let mut config = example_config();
+    fs::write(
+        archive_dir.join("176295.chk"),
+        checkpoint_blob(&config, 176295),
+    )
+    .unwrap();
+    config.object_store_fallback_urls = vec![
+        "memory:///".to_string(),
+        format!("file://{}", archive_dir.display()),
+    ];
+    let store = recorded_object_store(&config, &[90645]).await;
+    let client = LightClient::with_object_store(config, store).unwrap();
+
+    // Served by the object store, then by the archive after the empty fallback
+    for seq in [90645, 176295] {
+        let summary = client.download_checkpoint_summary(seq).await.unwrap();
+        assert_eq!(
+            summary.digest(),
+            client.read_checkpoint(seq).unwrap().digest()
+        );
+    }
+    let err = client
+        .download_checkpoint_summary(256378)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(LightClientError::CheckpointNotFound(256378))
+    ));
+    fs::remove_dir_all(&archive_dir).unwrap();
+}
+
+#[tokio::test]
+async fn checkpoints_are_read_at_the_path_template() {
+    let mut config = example_config();
+    config.object_store_path_template = "checkpoints/{seq:10}.chk".to_string();
+    let store = recorded_object_store(&config, &[256378]).await;
+    let client = LightClient::with_object_store(config, store).unwrap();
+
+    let summary = client.download_checkpoint_summary(256378).await.unwrap();
+    assert_eq!(summary.epoch(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn object_store_requests_are_rate_limited() {
+    let mut config = example_config();
+    config.object_store_rps = Some(2);
+    let store = recorded_object_store(&config, &[90645, 176295, 256378]).await;
+    let client = LightClient::with_object_store(config, store).unwrap();
+
+    // A burst of two requests, then one every half second. The clock is paused, so only
+    // the limiter's waits advance it
+    let start = tokio::time::Instant::now();
+    for seq in [90645, 176295] {
+        client.download_checkpoint_summary(seq).await.unwrap();
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+    for seq in [256378, 90645] {
+        client.download_checkpoint_summary(seq).await.unwrap();
+    }
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed >= Duration::from_secs(1) && elapsed < Duration::from_millis(1100),
+        "{:?}",
+        elapsed
+    );
+}
+
+/// A store whose first download of a blob drops the connection halfway through, recording
+/// the range of each download.
+#[derive(Debug)]
+struct FlakyStore {
+    inner: Box<dyn ObjectStore>,
+    ranges: Arc<Mutex<Vec<Option<GetRange>>>>,
+}
+
+impl fmt::Display for FlakyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FlakyStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for FlakyStore {
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        let first = {
+            let mut ranges = self.ranges.lock().unwrap();
+            ranges.push(options.range.clone());
+            ranges.len() == 1
+        };
+        let mut result = self.inner.get_opts(location, options).await?;
+        if first {
+            let meta = result.meta.clone();
+            let bytes = result.bytes().await?;
+            let half = Bytes::copy_from_slice(&bytes[..bytes.len() / 2]);
+            let dropped = object_store::Error::Generic {
+                store: "FlakyStore",
+                source: "connection dropped".into(),
+            };
+            result = GetResult {
+                payload: GetResultPayload::Stream(
+                    stream::iter(vec![Ok(half), Err(dropped)]).boxed(),
+                ),
+                meta,
+                range: 0..bytes.len(),
+                attributes: Default::default(),
+            };
+        }
+        Ok(result)
+    }
+
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        self.inner.put_multipart_opts(location, opts).await
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[tokio::test]
+async fn dropped_downloads_resume_from_the_bytes_received() {
+    let config = example_config();
+    let ranges = Arc::new(Mutex::new(vec![]));
+    let store = FlakyStore {
+        inner: recorded_object_store(&config, &[90645]).await,
+        ranges: ranges.clone(),
+    };
+    let client = LightClient::with_object_store(config.clone(), Box::new(store)).unwrap();
+
+    let full_checkpoint = client.get_full_checkpoint(90645).await.unwrap();
+    assert_eq!(
+        full_checkpoint.checkpoint_summary.digest(),
+        client.read_checkpoint(90645).unwrap().digest()
+    );
+
+    // The retry only requests the half that was not received
+    let half = checkpoint_blob(&config, 90645).len() / 2;
+    assert_eq!(
+        *ranges.lock().unwrap(),
+        vec![None, Some(GetRange::Offset(half))]
+    );
+}
+
+#[tokio::test]
+async fn checkpoints_are_synced_and_submitted_end_to_end() {
+    // Epochs 0 to 2 end at checkpoints 3, 7 and 11, the latest checkpoint is in epoch 3
+    let chain = TestChain::new(&[4, 4, 4, 1]);
+    let stub = Arc::new(StubNetwork::new(&chain));
+    let client = chain.client("end-to-end-sync", stub.clone()).await;
+
+    client
+        .check_and_sync_checkpoints(SyncOptions::default())
+        .await
+        .unwrap();
+    let list = client.read_checkpoint_list().unwrap();
+    assert_eq!(list.checkpoints, vec![3, 7, 11]);
+    assert_eq!(list.checkpoints, chain.end_of_epoch_checkpoints());
+
+    // The genesis committee was registered already, the next ones are submitted in order
+    assert_eq!(stub.registered_epochs(), vec![0, 1, 2]);
+    for epoch in 1..=3 {
+        assert_eq!(
+            client.read_committee(epoch).unwrap(),
+            chain.committees[epoch as usize]
+        );
+    }
+    let state = client.read_sync_state().unwrap();
+    assert_eq!(state.last_processed_checkpoint, Some(11));
+    assert_eq!(state.last_listed_checkpoint, Some(11));
+    assert_eq!(state.next_listed_epoch, Some(3));
+
+    // A second run finds nothing left to list or submit
+    client
+        .check_and_sync_checkpoints(SyncOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        client.read_checkpoint_list().unwrap().checkpoints,
+        list.checkpoints
+    );
+    assert_eq!(stub.registered_epochs(), vec![0, 1, 2]);
+    assert_eq!(
+        client.read_sync_state().unwrap().last_processed_checkpoint,
+        Some(11)
+    );
+}
+
+#[tokio::test]
+async fn tampered_committee_file_is_not_trusted() {
+    let chain = TestChain::new(&[2, 2, 2]);
+    let stub = Arc::new(StubNetwork::new(&chain));
+    let client = chain.client("tampered-committee", stub).await;
+    client
+        .check_and_sync_checkpoints(SyncOptions::default())
+        .await
+        .unwrap();
+
+    // Replace the cached committee of epoch 2 with validators that never signed it
+    let other = TestChain::new(&[1, 1, 1]);
+    client.write_committee(&other.committees[2]).unwrap();
+
+    let (effects, _) = client
+        .get_verified_effects_and_events_at(chain.transaction(5), 5)
+        .await
+        .unwrap();
+    assert_eq!(*effects.transaction_digest(), chain.transaction(5));
+    assert_eq!(client.read_committee(2).unwrap(), chain.committees[2]);
+}
+
+#[tokio::test]
+async fn dry_run_sync_registers_no_committee() {
+    let chain = TestChain::new(&[2, 2, 1]);
+    let stub = Arc::new(StubNetwork::new(&chain));
+    let client = chain.client("dry-run-sync", stub.clone()).await;
+
+    let options = SyncOptions {
+        dry_run: true,
+        ..Default::default()
+    };
+    client.check_and_sync_checkpoints(options).await.unwrap();
+    assert_eq!(
+        client.read_checkpoint_list().unwrap().checkpoints,
+        vec![1, 3]
+    );
+    assert_eq!(stub.registered_epochs(), vec![0]);
+    // The sync stops before processing the checkpoint whose committee it would submit
+    assert_eq!(
+        client.read_sync_state().unwrap().last_processed_checkpoint,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn end_of_epoch_checkpoint_is_verified_from_genesis() {
+    let chain = TestChain::new(&[2, 2, 2, 1]);
+    let stub = Arc::new(StubNetwork::new(&chain));
+    let client = chain.client("verified-end-of-epoch", stub.clone()).await;
+
+    // Nothing is stored, so the end of epochs 0 and 1 are downloaded on the way
+    let summary = client.verified_end_of_epoch_summary(2).await.unwrap();
+    assert_eq!(summary.sequence_number, 5);
+    assert!(client.verified_end_of_epoch_summary(3).await.is_err());
+
+    // Another genesis committee does not sign the chain
+    let other = TestChain::new(&[1]);
+    let client = chain
+        .client("unverified-end-of-epoch", stub)
+        .await
+        .with_genesis_committee(other.committees[0].clone());
+    assert!(client.verified_end_of_epoch_summary(2).await.is_err());
+}
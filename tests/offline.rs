@@ -0,0 +1,46 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::str::FromStr;
+
+use sui_light_client::LightClient;
+use sui_types::digests::TransactionDigest;
+
+mod common;
+
+use common::example_config;
+
+fn offline_client() -> LightClient {
+    let mut config = example_config();
+    config.offline = true;
+    LightClient::new(config).unwrap()
+}
+
+#[tokio::test]
+async fn network_access_fails_offline() {
+    let client = offline_client();
+
+    let err = client.query_last_checkpoint_of_epoch(0).await.unwrap_err();
+    assert!(format!("{:#}", err).contains("offline mode"));
+    let err = client.download_checkpoint_summary(90645).await.unwrap_err();
+    assert!(format!("{:#}", err).contains("offline mode"));
+
+    // Looking up the checkpoint of a transaction needs the full node
+    let tid = TransactionDigest::from_str("7DefdfmiEvb9de6LSKdD99xY7syZGJ3RzkP7XxHxcgc").unwrap();
+    let err = client
+        .get_verified_effects_and_events(tid)
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("offline mode"));
+}
+
+#[tokio::test]
+async fn missing_full_checkpoint_file_fails_offline() {
+    let client = offline_client();
+    let tid = TransactionDigest::from_str("7DefdfmiEvb9de6LSKdD99xY7syZGJ3RzkP7XxHxcgc").unwrap();
+    let err = client
+        .get_verified_effects_and_events_at(tid, 1)
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("Unable to read full checkpoint"));
+}
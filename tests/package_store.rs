@@ -0,0 +1,94 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use move_core_types::{account_address::AccountAddress, language_storage::TypeTag};
+use sui_light_client::{
+    CachingResolver, Config, LightClient, PackageCacheStats, RemotePackageStore,
+};
+use sui_package_resolver::PackageStore;
+
+mod common;
+
+use common::example_config;
+
+fn config(offline: bool) -> Config {
+    let mut config = example_config();
+    config.offline = offline;
+    config
+}
+
+fn client(offline: bool) -> Arc<LightClient> {
+    Arc::new(LightClient::new(config(offline)).unwrap())
+}
+
+fn coin_type() -> TypeTag {
+    TypeTag::from_str("0x2::coin::Coin<0x2::sui::SUI>").unwrap()
+}
+
+#[tokio::test]
+async fn resolution_fails_fast_offline() {
+    for store in [
+        RemotePackageStore::new(client(true)),
+        RemotePackageStore::for_resolution(client(true)),
+    ] {
+        let resolver = CachingResolver::new(store);
+        let res = tokio::time::timeout(Duration::from_secs(5), resolver.type_layout(coin_type()))
+            .await
+            .expect("resolution does not wait on itself");
+        assert!(res.is_err());
+    }
+}
+
+#[tokio::test]
+#[ignore = "fetches and authenticates the framework packages from the testnet full node"]
+async fn resolving_a_type_does_not_recurse() {
+    for store in [
+        RemotePackageStore::new(client(false)),
+        RemotePackageStore::for_resolution(client(false)),
+    ] {
+        let resolver = CachingResolver::new(store);
+        tokio::time::timeout(Duration::from_secs(120), resolver.type_layout(coin_type()))
+            .await
+            .expect("resolution does not recurse into itself")
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn package_cache_counts_misses_and_evicts_past_its_bound() {
+    let mut config = config(true);
+    config.max_cache_entries = Some(1);
+    let store = RemotePackageStore::for_resolution(Arc::new(LightClient::new(config).unwrap()));
+
+    // Offline fetches fail, and a failed fetch is tried again on the next request
+    assert!(store.fetch(AccountAddress::TWO).await.is_err());
+    assert!(store.fetch(AccountAddress::TWO).await.is_err());
+    assert_eq!(
+        store.cache_stats(),
+        PackageCacheStats {
+            hits: 0,
+            misses: 2,
+            evictions: 0,
+            entries: 1,
+        }
+    );
+
+    // A second package evicts the first one
+    assert!(store.fetch(AccountAddress::ONE).await.is_err());
+    let stats = store.cache_stats();
+    assert_eq!((stats.misses, stats.evictions, stats.entries), (3, 1, 1));
+}
+
+#[tokio::test]
+#[ignore = "fetches the framework packages from the testnet full node"]
+async fn resolving_a_type_again_hits_the_package_cache() {
+    let store = RemotePackageStore::for_resolution(client(false));
+    store.fetch(AccountAddress::TWO).await.unwrap();
+    let misses = store.cache_stats().misses;
+    store.fetch(AccountAddress::TWO).await.unwrap();
+    let stats = store.cache_stats();
+    assert_eq!(stats.misses, misses);
+    assert!(stats.hits >= 1);
+}
@@ -0,0 +1,85 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::sync::Arc;
+
+use sui_light_client::{
+    build_transaction_proof, genesis_committee, verify_transaction_proof, LightClient,
+    LightClientError, SyncOptions, TransactionProof,
+};
+use sui_types::{committee::Committee, digests::TransactionDigest};
+
+mod common;
+
+use common::{example_config, StubNetwork, TestChain};
+
+#[tokio::test]
+#[ignore = "downloads the last checkpoint of epoch 0 from the testnet object store"]
+async fn proof_verifies_after_a_round_trip() {
+    let config = example_config();
+    let client = LightClient::new(config.clone()).unwrap();
+
+    let seq = client.query_last_checkpoint_of_epoch(0).await.unwrap();
+    let checkpoint = client.get_full_checkpoint(seq).await.unwrap();
+    let tid = checkpoint
+        .checkpoint_contents
+        .iter()
+        .next()
+        .unwrap()
+        .transaction;
+    let committee = genesis_committee(&config).unwrap();
+    let proof = build_transaction_proof(&checkpoint, &committee, tid).unwrap();
+
+    let proof: TransactionProof = bcs::from_bytes(&bcs::to_bytes(&proof).unwrap()).unwrap();
+    assert_eq!(*proof.transaction_digest(), tid);
+    verify_transaction_proof(&proof, &committee).unwrap();
+
+    // A proof is only accepted against the committee it records
+    let epoch_1 = Committee::new(1, committee.voting_rights.iter().cloned().collect());
+    assert!(verify_transaction_proof(&proof, &epoch_1).is_err());
+}
+
+#[tokio::test]
+async fn failed_proofs_in_the_middle_of_a_batch_are_left_out() {
+    // Epochs 0 to 2 end at checkpoints 1, 3 and 5, epoch 3 has no committee registered
+    let chain = TestChain::new(&[2, 2, 2, 2]);
+    let stub = Arc::new(StubNetwork::new(&chain));
+    let client = chain.client("proof-batch", stub.clone()).await;
+    client
+        .check_and_sync_checkpoints(SyncOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(stub.registered_epochs(), vec![0, 1, 2]);
+
+    let unknown = TransactionDigest::random();
+    let tids = [
+        chain.transaction(2),
+        chain.transaction(6),
+        unknown,
+        chain.transaction(4),
+    ];
+    let submission = client
+        .submit_transaction_proofs(&tids, false)
+        .await
+        .unwrap();
+
+    assert!(submission.digest.is_some());
+    assert_eq!(
+        submission.submitted,
+        vec![chain.transaction(2), chain.transaction(4)]
+    );
+    assert_eq!(*stub.proofs.lock().unwrap(), submission.submitted);
+
+    assert_eq!(submission.failed.len(), 2);
+    let (tid, e) = &submission.failed[0];
+    assert_eq!(*tid, unknown);
+    assert!(matches!(
+        e.downcast_ref(),
+        Some(LightClientError::TransactionNotFound(_))
+    ));
+    let (tid, e) = &submission.failed[1];
+    assert_eq!(*tid, chain.transaction(6));
+    assert!(e
+        .to_string()
+        .contains("Committee of epoch 3 is not registered"));
+}
@@ -0,0 +1,281 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: BSD-3-Clause-Clear
+
+use std::sync::Arc;
+
+use sui_light_client::{
+    committee_from_stakes, diff_committees, extract_verified_effects_and_events,
+    extract_verified_sender_and_gas, extract_verified_transaction_data,
+    next_committee_from_summary, next_protocol_version_from_summary, verify_checkpoint_summary,
+    verify_checkpoint_transactions, verify_end_of_epoch_summary, CommitteeChange, LightClient,
+    LightClientError,
+};
+use sui_types::{
+    base_types::AuthorityName,
+    committee::{Committee, StakeUnit},
+    crypto::{get_authority_key_pair, AuthorityKeyPair, KeypairTraits},
+    digests::CheckpointContentsDigest,
+    effects::TransactionEffectsAPI,
+    gas::GasCostSummary,
+    messages_checkpoint::{CertifiedCheckpointSummary, CheckpointSummary, SignedCheckpointSummary},
+    transaction::TransactionDataAPI,
+};
+
+mod common;
+
+use common::{example_config, validator_keys, StubNetwork, TestChain};
+
+#[test]
+fn corrupted_contents_are_rejected() {
+    // Checkpoint 1 is the last of epoch 0, signed by the genesis committee
+    let chain = TestChain::new(&[2, 1]);
+    let committee = &chain.committees[0];
+    let mut checkpoint = chain.checkpoints[1].clone();
+    let tx = chain.transaction(1);
+    assert!(extract_verified_effects_and_events(&checkpoint, committee, tx).is_ok());
+
+    // Swap in the contents of the previous checkpoint, keeping the signed summary
+    checkpoint.checkpoint_contents = chain.checkpoints[0].checkpoint_contents.clone();
+    let err = extract_verified_effects_and_events(&checkpoint, committee, tx).unwrap_err();
+    assert!(err.to_string().contains("contents digest does not match"));
+
+    // The summary itself is still valid
+    assert!(verify_end_of_epoch_summary(&checkpoint.checkpoint_summary, committee).is_ok());
+}
+
+#[test]
+fn tampered_effects_are_rejected() {
+    let chain = TestChain::new(&[2]);
+    let committee = &chain.committees[0];
+    let mut checkpoint = chain.checkpoints[1].clone();
+    let digests = *checkpoint.checkpoint_contents.iter().next().unwrap();
+    assert!(
+        extract_verified_effects_and_events(&checkpoint, committee, digests.transaction).is_ok()
+    );
+
+    // Raise the gas used, leaving the effects digest listed in the signed contents as is
+    let effects = &mut checkpoint.transactions[0].effects;
+    effects.gas_cost_summary_mut_for_testing().computation_cost += 1;
+    assert_ne!(effects.execution_digests(), digests);
+    let err = extract_verified_effects_and_events(&checkpoint, committee, digests.transaction)
+        .unwrap_err();
+    assert!(matches!(err, LightClientError::VerificationFailed(_)));
+    assert!(err.to_string().contains("not found in checkpoint"));
+}
+
+#[test]
+fn sender_and_gas_are_read_from_the_verified_transaction() {
+    let chain = TestChain::new(&[2]);
+    let committee = &chain.committees[0];
+    let mut checkpoint = chain.checkpoints[1].clone();
+    let tx = chain.transaction(1);
+    let verified = extract_verified_sender_and_gas(&checkpoint, committee, tx).unwrap();
+    let data = checkpoint.transactions[0].transaction.transaction_data();
+    assert_eq!(verified.sender, data.sender());
+    assert_eq!(verified.gas_budget, data.gas_data().budget);
+
+    // Transaction data that is not the one listed in the contents is rejected
+    checkpoint.transactions[0].transaction =
+        chain.checkpoints[0].transactions[0].transaction.clone();
+    let err = extract_verified_sender_and_gas(&checkpoint, committee, tx).unwrap_err();
+    assert!(err.to_string().contains("has digest"));
+}
+
+#[tokio::test]
+async fn transaction_data_is_read_from_the_verified_transaction() {
+    let chain = TestChain::new(&[2, 1]);
+    let stub = Arc::new(StubNetwork::new(&chain));
+    let client = chain.client("verified-transaction", stub).await;
+
+    let committee = &chain.committees[0];
+    let checkpoint = &chain.checkpoints[1];
+    let tx = chain.transaction(1);
+    let data = extract_verified_transaction_data(checkpoint, committee, tx).unwrap();
+    assert_eq!(
+        &data,
+        checkpoint.transactions[0].transaction.transaction_data()
+    );
+
+    // The client looks up its checkpoint and derives the committee from genesis
+    let verified = client
+        .get_verified_transaction(tx, None, None)
+        .await
+        .unwrap();
+    assert_eq!(verified.data, data);
+    assert_eq!(verified.effects, checkpoint.transactions[0].effects);
+    assert_eq!(verified.committee, *committee);
+}
+
+#[test]
+fn every_transaction_of_a_checkpoint_is_verified() {
+    let chain = TestChain::new(&[2]);
+    let checkpoint = &chain.checkpoints[1];
+    let verified = verify_checkpoint_transactions(checkpoint, &chain.committees[0]).unwrap();
+
+    let digests: Vec<_> = checkpoint
+        .checkpoint_contents
+        .iter()
+        .map(|digests| digests.transaction)
+        .collect();
+    assert_eq!(digests.len(), 2);
+    assert_eq!(
+        verified.iter().map(|(tid, _, _)| *tid).collect::<Vec<_>>(),
+        digests
+    );
+}
+
+#[test]
+fn next_committee_verifies_the_following_checkpoint() {
+    let client = LightClient::new(example_config()).unwrap();
+
+    // The last checkpoint of epoch 1 hands over to the committee signing epoch 2
+    let summary = client.read_checkpoint(90645).unwrap();
+    let committee = next_committee_from_summary(&summary).unwrap();
+    assert_eq!(committee.epoch, summary.epoch() + 1);
+
+    let next = client.read_checkpoint(176295).unwrap();
+    let next_committee = verify_end_of_epoch_summary(&next, &committee).unwrap();
+    assert_eq!(next_committee, next_committee_from_summary(&next).unwrap());
+}
+
+#[test]
+fn summary_signed_in_another_epoch_is_rejected() {
+    let client = LightClient::new(example_config()).unwrap();
+    let summary = client.read_checkpoint(176295).unwrap();
+    let committee = next_committee_from_summary(&client.read_checkpoint(90645).unwrap()).unwrap();
+
+    // The summary claims the next epoch while keeping the signature of its own
+    let mut data = summary.data().clone();
+    data.epoch += 1;
+    let tampered =
+        CertifiedCheckpointSummary::new_from_data_and_sig(data, summary.auth_sig().clone());
+    let err = verify_checkpoint_summary(&tampered, &committee).unwrap_err();
+    let LightClientError::VerificationFailed(message) = err else {
+        panic!("expected a verification failure, got {}", err);
+    };
+    assert_eq!(
+        message,
+        "Checkpoint 176295 claims epoch 3 but is signed in epoch 2"
+    );
+}
+
+#[test]
+fn protocol_version_is_read_from_end_of_epoch_checkpoints_only() {
+    let client = LightClient::new(example_config()).unwrap();
+
+    let first = next_protocol_version_from_summary(&client.read_checkpoint(90645).unwrap());
+    let second = next_protocol_version_from_summary(&client.read_checkpoint(176295).unwrap());
+    assert!(first.unwrap() <= second.unwrap());
+
+    let mut summary = client.read_checkpoint(90645).unwrap().into_data();
+    summary.end_of_epoch_data = None;
+    assert!(matches!(
+        next_protocol_version_from_summary(&summary),
+        Err(LightClientError::VerificationFailed(_))
+    ));
+}
+
+/// A committee of `epoch` with the given stakes scaled to the total voting power, so e.g. 4
+/// validators can be given a stake of 1 each.
+fn small_committee(
+    epoch: u64,
+    stakes: impl IntoIterator<Item = (AuthorityName, StakeUnit)>,
+) -> Committee {
+    Committee::new_for_testing_with_normalized_voting_power(epoch, stakes.into_iter().collect())
+}
+
+/// A checkpoint of epoch 0 signed by the first `signers` of `keys`.
+fn signed_summary(
+    keys: &[(AuthorityName, AuthorityKeyPair)],
+    signers: usize,
+    committee: &Committee,
+) -> CertifiedCheckpointSummary {
+    let summary = CheckpointSummary {
+        epoch: 0,
+        sequence_number: 1,
+        network_total_transactions: 0,
+        content_digest: CheckpointContentsDigest::new([0; 32]),
+        previous_digest: None,
+        epoch_rolling_gas_cost_summary: GasCostSummary::default(),
+        timestamp_ms: 0,
+        checkpoint_commitments: vec![],
+        end_of_epoch_data: None,
+        version_specific_data: vec![],
+    };
+    let signatures = keys[..signers]
+        .iter()
+        .map(|(name, key)| {
+            SignedCheckpointSummary::new(0, summary.clone(), key, *name)
+                .auth_sig()
+                .clone()
+        })
+        .collect();
+    CertifiedCheckpointSummary::new(summary, signatures, committee).unwrap()
+}
+
+#[test]
+fn small_committee_verifies_hand_signed_summary() {
+    let keys = validator_keys(4);
+
+    // Equal stakes: 3 of the 4 validators reach the quorum
+    let committee = small_committee(0, keys.iter().map(|(name, _)| (*name, 1)));
+    let summary = signed_summary(&keys, 3, &committee);
+    verify_checkpoint_summary(&summary, &committee).unwrap();
+
+    // The same signers hold too little stake once the last validator holds most of it
+    let skewed = small_committee(
+        0,
+        keys.iter()
+            .enumerate()
+            .map(|(i, (name, _))| (*name, if i == 3 { 7 } else { 1 })),
+    );
+    let err = verify_checkpoint_summary(&summary, &skewed).unwrap_err();
+    let LightClientError::InvalidSignature { diagnostic, .. } = err else {
+        panic!("expected an invalid signature, got {}", err);
+    };
+    assert!(diagnostic.signed_stake < diagnostic.quorum_threshold);
+    assert_eq!(diagnostic.cause(), "insufficient stake");
+}
+
+#[test]
+fn committee_diff_lists_added_removed_and_reweighted_validators() {
+    let mut names: Vec<AuthorityName> = (0..4)
+        .map(|_| get_authority_key_pair().1.public().into())
+        .collect();
+    names.sort();
+
+    let from =
+        committee_from_stakes(1, [(names[0], 4000), (names[1], 3000), (names[2], 3000)]).unwrap();
+    assert_eq!(diff_committees(&from, &from), vec![]);
+
+    let to =
+        committee_from_stakes(2, [(names[0], 4000), (names[1], 5000), (names[3], 1000)]).unwrap();
+    let changes = diff_committees(&from, &to);
+    assert_eq!(changes.len(), 3);
+    assert!(matches!(
+        changes[0],
+        CommitteeChange::StakeChanged { name, from, to } if name == names[1] && from < to
+    ));
+    assert!(matches!(changes[1], CommitteeChange::Added { name, .. } if name == names[3]));
+    assert!(matches!(changes[2], CommitteeChange::Removed { name, .. } if name == names[2]));
+}
+
+#[test]
+fn committee_stakes_must_sum_to_the_total_voting_power() {
+    let names: Vec<AuthorityName> = (0..2)
+        .map(|_| get_authority_key_pair().1.public().into())
+        .collect();
+
+    for stakes in [
+        vec![],
+        vec![(names[0], 1), (names[1], 1)],
+        vec![(names[0], u64::MAX), (names[1], 1)],
+    ] {
+        assert!(matches!(
+            committee_from_stakes(1, stakes),
+            Err(LightClientError::VerificationFailed(_))
+        ));
+    }
+    let committee = committee_from_stakes(1, [(names[0], 5000), (names[1], 5000)]).unwrap();
+    assert_eq!(committee.total_votes(), 10000);
+}